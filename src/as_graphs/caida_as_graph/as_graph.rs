@@ -1,179 +1,347 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Index;
 
-use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink, PeerLink};
-use crate::as_graphs::caida_as_graph::bgp::{BGP};
+use serde::{Deserialize, Serialize};
 
+use crate::as_graphs::caida_as_graph::{ASGraphInfo};
+use crate::as_graphs::caida_as_graph::as_group::{ASGroup, NUM_TIER_1_CANDIDATES};
+use crate::as_graphs::caida_as_graph::classification::ASRole;
 
-#[derive(Debug)]
+
+// Dense index of an AutonomousSystem within its ASGraph.
+// Indices are assigned in ascending ASN order, so they are stable for a given ASGraphInfo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct AsIdx(pub u32);
+
+impl AsIdx {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutonomousSystem {
     pub asn: u32,
+    pub idx: AsIdx,
     pub input_clique: bool,
     pub ixp: bool,
-    pub peers: Vec<*mut AutonomousSystem>, // Mutable raw pointers to allow modification
-    pub providers: Vec<*mut AutonomousSystem>,
-    pub customers: Vec<*mut AutonomousSystem>,
+    pub peers: Vec<AsIdx>,
+    pub providers: Vec<AsIdx>,
+    pub customers: Vec<AsIdx>,
     pub propagation_rank: Option<u32>,
-    pub policy: BGP,
 }
 
 impl AutonomousSystem {
-    pub fn new(asn: u32, input_clique: bool, ixp: bool) -> Self {
-        let mut autonomous_system = Self {
+    pub fn new(asn: u32, idx: AsIdx, input_clique: bool, ixp: bool) -> Self {
+        Self {
             asn,
+            idx,
             input_clique,
             ixp,
             peers: Vec::new(),
             providers: Vec::new(),
             customers: Vec::new(),
             propagation_rank: None,
-            policy: BGP::new(std::ptr::null()), // Temporarily set to null
-        };
-        // Correctly set the policy to reference this AutonomousSystem
-        autonomous_system.policy = BGP::new(&autonomous_system as *const AutonomousSystem);
-
-        autonomous_system
+        }
     }
 
-    pub fn is_stub(&self) -> bool {
-        self.neighbors().len() == 1
+    pub fn neighbors(&self) -> Vec<AsIdx> {
+        let mut neighbors = Vec::new();
+        neighbors.extend(&self.customers);
+        neighbors.extend(&self.peers);
+        neighbors.extend(&self.providers);
+        neighbors
     }
 
-    pub fn is_multihomed(&self) -> bool {
-        self.customers.is_empty() && self.peers.len() + self.providers.len() > 1
+    pub fn neighbor_asns(&self, as_graph: &ASGraph) -> Vec<u32> {
+        self.neighbors().into_iter().map(|idx| as_graph[idx].asn).collect()
     }
+}
 
-    pub fn is_transit(&self) -> bool {
-        !self.customers.is_empty() && (self.customers.len() + self.peers.len() + self.providers.len() > 1)
-    }
+// What to do when customer -> provider links form a cycle, which leaves no valid propagation order
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CycleHandling {
+    // Fail with a CustomerProviderCycleError
+    ERROR,
+    // Every customer -> provider link between two ASes of the same cycle
+    // (strongly connected component) becomes a peer link instead. ASes that are
+    // each other's providers are most likely siblings or peers mislabeled in the
+    // input, and links into or out of the cycle are left alone
+    BREAK_INTO_PEERS,
+}
 
-    pub fn neighbors(&self) -> Vec<u32> {
-        let mut neighbors = Vec::new();
-        neighbors.extend(self.customers.iter().map(|&ptr| unsafe { (*ptr).asn }));
-        neighbors.extend(self.peers.iter().map(|&ptr| unsafe { (*ptr).asn }));
-        neighbors.extend(self.providers.iter().map(|&ptr| unsafe { (*ptr).asn }));
-        neighbors
-    }
+// The ASNs of each customer -> provider cycle, sorted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomerProviderCycleError {
+    pub cycles: Vec<Vec<u32>>,
+}
 
-    pub fn neighbor_asns(&self) -> Vec<u32> {
-        self.neighbors()
+impl fmt::Display for CustomerProviderCycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} customer-provider cycle(s) between ASNs", self.cycles.len())?;
+        for (i, cycle) in self.cycles.iter().enumerate() {
+            let sep = if i == 0 { " " } else { "; " };
+            write!(f, "{}{:?}", sep, cycle)?;
+        }
+        Ok(())
     }
 }
 
+impl std::error::Error for CustomerProviderCycleError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ASGraph {
-    pub as_dict: HashMap<u32, Box<AutonomousSystem>>,
-    pub as_groups: HashMap<String, HashSet<u32>>,
+    as_list: Vec<AutonomousSystem>,
+    asn_to_idx: HashMap<u32, AsIdx>,
+    // The built-in groups and any loaded CUSTOM groups, see ASGraph::group_asns
+    pub as_groups: HashMap<ASGroup, HashSet<u32>>,
 }
 
 impl ASGraph {
+    // Panics if the customer -> provider links contain a cycle, see try_new
     pub fn new(as_graph_info: ASGraphInfo) -> Self {
+        Self::try_new(as_graph_info, CycleHandling::ERROR).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new(as_graph_info: ASGraphInfo, cycle_handling: CycleHandling) -> Result<Self, CustomerProviderCycleError> {
         let mut graph = ASGraph {
-            as_dict: HashMap::new(),
+            as_list: Vec::new(),
+            asn_to_idx: HashMap::new(),
             as_groups: HashMap::new(),
         };
 
         graph.generate_graph(&as_graph_info);
         graph.add_relationships(&as_graph_info);
-        graph.assign_propagation_ranks();
+        if !graph.assign_propagation_ranks() {
+            let cycles = graph.customer_provider_cycles();
+            match cycle_handling {
+                CycleHandling::ERROR => {
+                    return Err(CustomerProviderCycleError {
+                        cycles: cycles
+                            .iter()
+                            .map(|cycle| cycle.iter().map(|&idx| graph[idx].asn).collect())
+                            .collect(),
+                    });
+                }
+                CycleHandling::BREAK_INTO_PEERS => {
+                    log::warn!("Breaking {} customer-provider cycle(s) into peer links", cycles.len());
+                    graph.break_cycles_into_peers(&cycles);
+                    let ranked = graph.assign_propagation_ranks();
+                    debug_assert!(ranked, "No cycles are left after breaking them");
+                }
+            }
+        }
         graph.set_as_groups();
-        graph
+        Ok(graph)
     }
-    fn generate_graph(&mut self, as_graph_info: &ASGraphInfo) {
-        fn gen_as(asn: u32, graph: &mut ASGraph) -> *mut AutonomousSystem {
-            let as_obj = Box::new(AutonomousSystem::new(asn, false, false));
-            let ptr = Box::into_raw(as_obj);
-            graph.as_dict.insert(asn, unsafe { Box::from_raw(ptr) });
-            ptr
-        }
 
-        for asn in as_graph_info.asns() {
-            if !self.as_dict.contains_key(&asn) {
-                gen_as(asn, self);
-            }
-        }
+    pub fn len(&self) -> usize {
+        self.as_list.len()
+    }
 
-        for &asn in &as_graph_info.ixp_asns {
-            let as_ptr = if let Some(as_obj) = self.as_dict.get_mut(&asn) {
-                as_obj.as_mut() as *mut _
-            } else {
-                gen_as(asn, self)
-            };
-            unsafe {
-                (*as_ptr).ixp = true;
-            }
-        }
+    pub fn is_empty(&self) -> bool {
+        self.as_list.is_empty()
+    }
 
-        for &asn in &as_graph_info.input_clique_asns {
-            let as_ptr = if let Some(as_obj) = self.as_dict.get_mut(&asn) {
-                as_obj.as_mut() as *mut _
-            } else {
-                gen_as(asn, self)
-            };
-            unsafe {
-                (*as_ptr).input_clique = true;
-            }
+    pub fn iter(&self) -> std::slice::Iter<'_, AutonomousSystem> {
+        self.as_list.iter()
+    }
+
+    pub fn asns(&self) -> impl Iterator<Item = u32> + '_ {
+        self.as_list.iter().map(|as_obj| as_obj.asn)
+    }
+
+    pub fn idx(&self, asn: u32) -> Option<AsIdx> {
+        self.asn_to_idx.get(&asn).copied()
+    }
+
+    pub fn get(&self, asn: u32) -> Option<&AutonomousSystem> {
+        self.idx(asn).map(|idx| &self[idx])
+    }
+
+    pub fn contains(&self, asn: u32) -> bool {
+        self.asn_to_idx.contains_key(&asn)
+    }
+
+    fn generate_graph(&mut self, as_graph_info: &ASGraphInfo) {
+        let mut asns: Vec<u32> = as_graph_info.asns().into_iter().collect();
+        asns.sort_unstable();
+
+        for asn in asns {
+            let idx = AsIdx(self.as_list.len() as u32);
+            let as_obj = AutonomousSystem::new(
+                asn,
+                idx,
+                as_graph_info.input_clique_asns.contains(&asn),
+                as_graph_info.ixp_asns.contains(&asn),
+            );
+            self.as_list.push(as_obj);
+            self.asn_to_idx.insert(asn, idx);
         }
     }
 
     fn add_relationships(&mut self, as_graph_info: &ASGraphInfo) {
-        let mut temp_relations = vec![];
-
         for link in &as_graph_info.customer_provider_links {
-            temp_relations.push((link.customer_asn, link.provider_asn));
+            let customer = self.asn_to_idx[&link.customer_asn];
+            let provider = self.asn_to_idx[&link.provider_asn];
+            self.as_list[customer.index()].providers.push(provider);
+            self.as_list[provider.index()].customers.push(customer);
         }
 
-        for (customer_asn, provider_asn) in temp_relations {
-            let customer: *mut AutonomousSystem = self.as_dict.get_mut(&customer_asn).unwrap().as_mut() as *mut _;
-            let provider: *mut AutonomousSystem = self.as_dict.get_mut(&provider_asn).unwrap().as_mut() as *mut _;
-
-            unsafe {
-                (*customer).providers.push(provider);
-                (*provider).customers.push(customer);
-            }
+        for link in &as_graph_info.peer_links {
+            let peer1 = self.asn_to_idx[&link.peer1_asn];
+            let peer2 = self.asn_to_idx[&link.peer2_asn];
+            self.as_list[peer1.index()].peers.push(peer2);
+            self.as_list[peer2.index()].peers.push(peer1);
         }
 
-        let mut temp_peers = vec![];
-        for link in &as_graph_info.peer_links {
-            temp_peers.push((link.peer1_asn, link.peer2_asn));
+        // Links come from HashSets, so sort them to keep neighbor order deterministic
+        for as_obj in &mut self.as_list {
+            as_obj.providers.sort_unstable();
+            as_obj.customers.sort_unstable();
+            as_obj.peers.sort_unstable();
         }
+    }
 
-        for (peer1_asn, peer2_asn) in temp_peers {
-            let peer1: *mut AutonomousSystem = self.as_dict.get_mut(&peer1_asn).unwrap().as_mut() as *mut _;
-            let peer2: *mut AutonomousSystem = self.as_dict.get_mut(&peer2_asn).unwrap().as_mut() as *mut _;
+    // An AS's rank is the length of the longest chain of customers below it, so
+    // stubs are rank 0 and every AS ranks above all of its customers.
+    // Ranked iteratively from the bottom up (Kahn's algorithm): an AS is ranked once all of
+    // its customers are. Returns false, leaving ranks unset, if some ASes could never be
+    // ranked because they're in (or above) a customer -> provider cycle
+    fn assign_propagation_ranks(&mut self) -> bool {
+        let mut unranked_customers: Vec<usize> = self.as_list.iter().map(|as_obj| as_obj.customers.len()).collect();
+        let mut ranks = vec![0u32; self.as_list.len()];
+        let mut ready: Vec<usize> = (0..self.as_list.len())
+            .filter(|&i| unranked_customers[i] == 0)
+            .collect();
 
-            unsafe {
-                (*peer1).peers.push(peer2);
-                (*peer2).peers.push(peer1);
+        let mut num_ranked = 0;
+        while let Some(i) = ready.pop() {
+            num_ranked += 1;
+            for provider in &self.as_list[i].providers {
+                let provider = provider.index();
+                ranks[provider] = ranks[provider].max(ranks[i] + 1);
+                unranked_customers[provider] -= 1;
+                if unranked_customers[provider] == 0 {
+                    ready.push(provider);
+                }
             }
         }
-    }
 
-    fn assign_propagation_ranks(&mut self) {
-        let as_nodes: Vec<*mut AutonomousSystem> = self
-            .as_dict
-            .values_mut()
-            .map(|as_node| as_node.as_mut() as *mut _)
-            .collect();
+        if num_ranked < self.as_list.len() {
+            return false;
+        }
+        for (as_obj, rank) in self.as_list.iter_mut().zip(ranks) {
+            as_obj.propagation_rank = Some(rank);
+        }
+        true
+    }
 
-        for as_ptr in as_nodes {
-            unsafe {
-                self.assign_rank_recursive(as_ptr, 0);
+    // Strongly connected components of the customer -> provider graph that contain a
+    // cycle, each sorted, in ascending order. Iterative Tarjan, since the graph is
+    // too large to recurse over
+    fn customer_provider_cycles(&self) -> Vec<Vec<AsIdx>> {
+        let unvisited = u32::MAX;
+        let mut index = vec![unvisited; self.as_list.len()];
+        let mut lowlink = vec![0u32; self.as_list.len()];
+        let mut on_stack = vec![false; self.as_list.len()];
+        let mut stack: Vec<usize> = Vec::new();
+        let mut next_index = 0;
+        let mut cycles = Vec::new();
+
+        for root in 0..self.as_list.len() {
+            if index[root] != unvisited {
+                continue;
+            }
+            // (AS, position of the next provider to visit)
+            let mut call_stack = vec![(root, 0)];
+            index[root] = next_index;
+            lowlink[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(&(i, pos)) = call_stack.last() {
+                let providers = &self.as_list[i].providers;
+                if pos < providers.len() {
+                    call_stack.last_mut().unwrap().1 += 1;
+                    let provider = providers[pos].index();
+                    if index[provider] == unvisited {
+                        index[provider] = next_index;
+                        lowlink[provider] = next_index;
+                        next_index += 1;
+                        stack.push(provider);
+                        on_stack[provider] = true;
+                        call_stack.push((provider, 0));
+                    } else if on_stack[provider] {
+                        lowlink[i] = lowlink[i].min(index[provider]);
+                    }
+                    continue;
+                }
+
+                call_stack.pop();
+                if let Some(&(parent, _)) = call_stack.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[i]);
+                }
+                if lowlink[i] == index[i] {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack[member] = false;
+                        component.push(AsIdx(member as u32));
+                        if member == i {
+                            break;
+                        }
+                    }
+                    // A single AS is only a cycle if it's its own provider
+                    if component.len() > 1 || providers.contains(&AsIdx(i as u32)) {
+                        component.sort_unstable();
+                        cycles.push(component);
+                    }
+                }
             }
         }
+
+        cycles.sort_unstable();
+        cycles
     }
 
-    unsafe fn assign_rank_recursive(&self, as_node: *mut AutonomousSystem, rank: u32) {
-        if (*as_node).propagation_rank.is_none() || (*as_node).propagation_rank.unwrap() < rank {
-            (*as_node).propagation_rank = Some(rank);
-            for &provider in &(*as_node).providers {
-                self.assign_rank_recursive(provider, rank + 1);
+    // See CycleHandling::BREAK_INTO_PEERS. An AS that is its own provider just loses the link
+    fn break_cycles_into_peers(&mut self, cycles: &[Vec<AsIdx>]) {
+        for cycle in cycles {
+            let members: HashSet<AsIdx> = cycle.iter().copied().collect();
+            for &idx in cycle {
+                let as_obj = &mut self.as_list[idx.index()];
+                let (in_cycle, providers): (Vec<AsIdx>, Vec<AsIdx>) =
+                    as_obj.providers.iter().partition(|provider| members.contains(provider));
+                as_obj.providers = providers;
+                as_obj.customers.retain(|customer| !members.contains(customer));
+                as_obj.peers.extend(in_cycle.into_iter().filter(|&peer| peer != idx));
+            }
+        }
+
+        // Each link was added as a peer from the customer's side only
+        let mut peer_links = Vec::new();
+        for as_obj in &self.as_list {
+            for &peer in &as_obj.peers {
+                peer_links.push((peer, as_obj.idx));
             }
         }
+        for (idx, peer) in peer_links {
+            self.as_list[idx.index()].peers.push(peer);
+        }
+        for as_obj in &mut self.as_list {
+            as_obj.peers.sort_unstable();
+            as_obj.peers.dedup();
+        }
     }
 
     pub fn get_propagation_ranks(&self) -> Vec<Vec<u32>> {
         let mut rank_buckets: HashMap<u32, Vec<u32>> = HashMap::new();
 
-        for as_node in self.as_dict.values() {
+        for as_node in &self.as_list {
             if let Some(rank) = as_node.propagation_rank {
                 rank_buckets.entry(rank).or_default().push(as_node.asn);
             }
@@ -182,41 +350,133 @@ impl ASGraph {
         let mut sorted_ranks: Vec<_> = rank_buckets.into_iter().collect();
         sorted_ranks.sort_by_key(|&(rank, _)| rank);
 
-        sorted_ranks.into_iter().map(|(_, nodes)| nodes).collect()
+        sorted_ranks
+            .into_iter()
+            .map(|(_, mut nodes)| {
+                // Sorted so that propagation order is deterministic
+                nodes.sort_unstable();
+                nodes
+            })
+            .collect()
     }
 
     fn set_as_groups(&mut self) {
-        let ixp_asns: HashSet<u32> = self
-            .as_dict
-            .values()
-            .filter(|as_obj| as_obj.ixp)
-            .map(|as_obj| as_obj.asn)
-            .collect();
+        let group = |filter: &dyn Fn(&AutonomousSystem) -> bool| -> HashSet<u32> {
+            self.as_list
+                .iter()
+                .filter(|as_obj| filter(as_obj))
+                .map(|as_obj| as_obj.asn)
+                .collect()
+        };
 
-        let stubs: HashSet<u32> = self
-            .as_dict
-            .values()
-            .filter(|as_obj| as_obj.is_stub())
-            .map(|as_obj| as_obj.asn)
+        let groups = [
+            (ASGroup::ALL, group(&|_| true)),
+            (ASGroup::IXPS, group(&|as_obj| as_obj.ixp)),
+            (ASGroup::STUBS, group(&|as_obj| as_obj.is_stub())),
+            (ASGroup::MULTIHOMED, group(&|as_obj| as_obj.is_multihomed())),
+            (ASGroup::STUBS_OR_MH, group(&|as_obj| matches!(as_obj.role(), ASRole::STUB | ASRole::MULTIHOMED))),
+            (ASGroup::TRANSIT, group(&|as_obj| as_obj.is_transit())),
+            (ASGroup::INPUT_CLIQUE, group(&|as_obj| as_obj.input_clique)),
+            (ASGroup::ETR, group(&|as_obj| !as_obj.input_clique)),
+        ];
+
+        let tier_1: HashSet<u32> = self
+            .top_customer_cones(NUM_TIER_1_CANDIDATES)
+            .into_iter()
+            .filter(|&asn| self.get(asn).unwrap().providers.is_empty())
             .collect();
 
-        let multihomed: HashSet<u32> = self
-            .as_dict
-            .values()
-            .filter(|as_obj| as_obj.is_multihomed())
-            .map(|as_obj| as_obj.asn)
-            .collect();
+        self.as_groups.extend(groups);
+        self.as_groups.insert(ASGroup::TIER_1, tier_1);
+    }
+}
 
-        let transit: HashSet<u32> = self
-            .as_dict
-            .values()
-            .filter(|as_obj| as_obj.is_transit())
-            .map(|as_obj| as_obj.asn)
-            .collect();
+impl Index<AsIdx> for ASGraph {
+    type Output = AutonomousSystem;
+
+    fn index(&self, idx: AsIdx) -> &AutonomousSystem {
+        &self.as_list[idx.index()]
+    }
+}
+
+impl<'a> IntoIterator for &'a ASGraph {
+    type Item = &'a AutonomousSystem;
+    type IntoIter = std::slice::Iter<'a, AutonomousSystem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::as_graphs::caida_as_graph::CustomerProviderLink;
+
+    fn as_graph_info(customer_provider_links: &[(u32, u32)]) -> ASGraphInfo {
+        ASGraphInfo {
+            customer_provider_links: customer_provider_links
+                .iter()
+                .map(|&(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: HashSet::new(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        }
+    }
+
+    fn neighbor_asns(as_graph: &ASGraph, idxs: &[AsIdx]) -> Vec<u32> {
+        idxs.iter().map(|&idx| as_graph[idx].asn).collect()
+    }
+
+    #[test]
+    fn ranks_are_longest_customer_chains() {
+        // 1 reaches stub 4 through 2 and 3, and stub 5 directly
+        let as_graph = ASGraph::new(as_graph_info(&[(4, 3), (3, 2), (2, 1), (5, 1), (6, 3)]));
+        assert_eq!(as_graph.get_propagation_ranks(), vec![vec![4, 5, 6], vec![3], vec![2], vec![1]]);
+    }
+
+    #[test]
+    fn deep_chains_dont_overflow_the_stack() {
+        let links: Vec<(u32, u32)> = (0..100_000).map(|asn| (asn, asn + 1)).collect();
+        let as_graph = ASGraph::new(as_graph_info(&links));
+        assert_eq!(as_graph.get(100_000).unwrap().propagation_rank, Some(100_000));
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        // A 3-cycle with ASes hanging off it, a 2-cycle and an AS that's its own provider
+        let links = [(1, 2), (2, 3), (3, 1), (4, 1), (3, 5), (7, 8), (8, 7), (9, 9)];
+        let err = ASGraph::try_new(as_graph_info(&links), CycleHandling::ERROR).unwrap_err();
+        assert_eq!(err.cycles, vec![vec![1, 2, 3], vec![7, 8], vec![9]]);
+        assert_eq!(
+            err.to_string(),
+            "3 customer-provider cycle(s) between ASNs [1, 2, 3]; [7, 8]; [9]"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "customer-provider cycle(s) between ASNs [1, 2]")]
+    fn new_panics_on_cycles() {
+        ASGraph::new(as_graph_info(&[(1, 2), (2, 1)]));
+    }
 
-        self.as_groups.insert("ixps".to_string(), ixp_asns);
-        self.as_groups.insert("stubs".to_string(), stubs);
-        self.as_groups.insert("multihomed".to_string(), multihomed);
-        self.as_groups.insert("transit".to_string(), transit);
+    #[test]
+    fn cycles_can_be_broken_into_peers() {
+        let links = [(1, 2), (2, 3), (3, 1), (4, 1), (3, 5), (7, 8), (8, 7), (9, 9)];
+        let as_graph = ASGraph::try_new(as_graph_info(&links), CycleHandling::BREAK_INTO_PEERS).unwrap();
+        let as_obj = |asn| as_graph.get(asn).unwrap();
+
+        // Links inside a cycle become peer links, links into or out of it are kept
+        assert_eq!(neighbor_asns(&as_graph, &as_obj(1).peers), vec![2, 3]);
+        assert_eq!(neighbor_asns(&as_graph, &as_obj(1).customers), vec![4]);
+        assert!(as_obj(1).providers.is_empty());
+        assert_eq!(neighbor_asns(&as_graph, &as_obj(3).providers), vec![5]);
+        assert_eq!(neighbor_asns(&as_graph, &as_obj(7).peers), vec![8]);
+        assert_eq!(neighbor_asns(&as_graph, &as_obj(8).peers), vec![7]);
+        // An AS that's its own provider just loses the link
+        assert!(as_obj(9).neighbors().is_empty());
+
+        assert_eq!(as_graph.get_propagation_ranks(), vec![vec![2, 3, 4, 7, 8, 9], vec![1, 5]]);
     }
 }
@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::as_graphs::caida_as_graph::ASGraph;
+
+// How many of the largest customer cones TIER_1 is picked from
+pub const NUM_TIER_1_CANDIDATES: usize = 20;
+
+// A set of ASes in an ASGraph. The built-in groups are computed when the graph is
+// built, CUSTOM groups are loaded with ASGraph::load_as_groups (e.g. a list of
+// ASes known to deploy ROV), and UNION, INTERSECTION and DIFFERENCE combine groups
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ASGroup {
+    ALL,
+    IXPS,
+    // ASes whose ASRole is STUB, MULTIHOMED, either, or TRANSIT.
+    // ISOLATED ASes are in none of these
+    STUBS,
+    MULTIHOMED,
+    STUBS_OR_MH,
+    TRANSIT,
+    INPUT_CLIQUE,
+    // Everything but the input clique
+    ETR,
+    // ASes without providers among the NUM_TIER_1_CANDIDATES
+    // largest customer cones (see ASGraph::top_customer_cones)
+    TIER_1,
+    CUSTOM(String),
+    UNION(Vec<ASGroup>),
+    INTERSECTION(Vec<ASGroup>),
+    DIFFERENCE(Box<ASGroup>, Box<ASGroup>),
+}
+
+impl ASGroup {
+    // Groups that every ASGraph has
+    pub const BUILT_IN: [ASGroup; 9] = [
+        ASGroup::ALL,
+        ASGroup::IXPS,
+        ASGroup::STUBS,
+        ASGroup::MULTIHOMED,
+        ASGroup::STUBS_OR_MH,
+        ASGroup::TRANSIT,
+        ASGroup::INPUT_CLIQUE,
+        ASGroup::ETR,
+        ASGroup::TIER_1,
+    ];
+
+    pub fn union(self, other: ASGroup) -> ASGroup {
+        ASGroup::UNION(vec![self, other])
+    }
+
+    pub fn intersection(self, other: ASGroup) -> ASGroup {
+        ASGroup::INTERSECTION(vec![self, other])
+    }
+
+    // ASes in self but not in other
+    pub fn difference(self, other: ASGroup) -> ASGroup {
+        ASGroup::DIFFERENCE(Box::new(self), Box::new(other))
+    }
+}
+
+impl fmt::Display for ASGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, groups: &[ASGroup], op: &str| {
+            let names: Vec<String> = groups.iter().map(|group| group.to_string()).collect();
+            write!(f, "({})", names.join(op))
+        };
+        match self {
+            ASGroup::ALL => write!(f, "all"),
+            ASGroup::IXPS => write!(f, "ixps"),
+            ASGroup::STUBS => write!(f, "stubs"),
+            ASGroup::MULTIHOMED => write!(f, "multihomed"),
+            ASGroup::STUBS_OR_MH => write!(f, "stubs_or_mh"),
+            ASGroup::TRANSIT => write!(f, "transit"),
+            ASGroup::INPUT_CLIQUE => write!(f, "input_clique"),
+            ASGroup::ETR => write!(f, "etr"),
+            ASGroup::TIER_1 => write!(f, "tier_1"),
+            ASGroup::CUSTOM(name) => write!(f, "{}", name),
+            ASGroup::UNION(groups) => join(f, groups, " | "),
+            ASGroup::INTERSECTION(groups) => join(f, groups, " & "),
+            ASGroup::DIFFERENCE(group, other) => write!(f, "({} - {})", group, other),
+        }
+    }
+}
+
+// Names of built-in groups parse to that group, and anything else to a CUSTOM group
+impl FromStr for ASGroup {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ASGroup::BUILT_IN
+            .into_iter()
+            .find(|group| group.to_string() == s)
+            .unwrap_or_else(|| ASGroup::CUSTOM(s.to_string())))
+    }
+}
+
+impl ASGraph {
+    // ASNs in group, or None if it is (or combines) a CUSTOM group that was never loaded
+    pub fn group_asns(&self, group: &ASGroup) -> Option<HashSet<u32>> {
+        match group {
+            ASGroup::UNION(groups) => {
+                let mut asns = HashSet::new();
+                for group in groups {
+                    asns.extend(self.group_asns(group)?);
+                }
+                Some(asns)
+            }
+            ASGroup::INTERSECTION(groups) => {
+                let mut asns: Option<HashSet<u32>> = None;
+                for group in groups {
+                    let group_asns = self.group_asns(group)?;
+                    asns = Some(match asns {
+                        Some(asns) => asns.intersection(&group_asns).copied().collect(),
+                        None => group_asns,
+                    });
+                }
+                Some(asns.unwrap_or_default())
+            }
+            ASGroup::DIFFERENCE(group, other) => {
+                let other_asns = self.group_asns(other)?;
+                Some(self.group_asns(group)?.difference(&other_asns).copied().collect())
+            }
+            _ => self.as_groups.get(group).cloned(),
+        }
+    }
+
+    // Adds CUSTOM groups from a JSON file mapping group names to lists of ASNs, e.g.
+    // {"rov_adopters": [13335, 174]}. ASNs that aren't in the graph are left out
+    pub fn load_as_groups(&mut self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let groups: HashMap<String, Vec<u32>> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if let Some(name) = groups
+            .keys()
+            .find(|name| ASGroup::BUILT_IN.iter().any(|group| group.to_string() == **name))
+        {
+            return Err(format!("{} is a built-in AS group", name).into());
+        }
+
+        for (name, asns) in groups {
+            let asns: HashSet<u32> = asns.into_iter().filter(|&asn| self.contains(asn)).collect();
+            self.as_groups.insert(ASGroup::CUSTOM(name), asns);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink};
+
+    fn as_graph() -> ASGraph {
+        ASGraph::new(ASGraphInfo {
+            customer_provider_links: [(1, 2), (3, 2), (4, 3)]
+                .into_iter()
+                .map(|(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: HashSet::new(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        })
+    }
+
+    fn load(as_graph: &mut ASGraph, json: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("as_groups.json");
+        fs::write(&path, json).unwrap();
+        as_graph.load_as_groups(&path)
+    }
+
+    fn custom(name: &str) -> ASGroup {
+        ASGroup::CUSTOM(name.to_string())
+    }
+
+    #[test]
+    fn custom_groups_combine() {
+        let mut as_graph = as_graph();
+        // 9 isn't in the graph, so it's left out
+        load(&mut as_graph, r#"{"a": [1, 2, 9], "b": [2, 3]}"#).unwrap();
+
+        assert_eq!(as_graph.group_asns(&custom("a")), Some(HashSet::from([1, 2])));
+        assert_eq!(as_graph.group_asns(&custom("a").union(custom("b"))), Some(HashSet::from([1, 2, 3])));
+        assert_eq!(as_graph.group_asns(&custom("a").intersection(custom("b"))), Some(HashSet::from([2])));
+        assert_eq!(as_graph.group_asns(&custom("a").difference(custom("b"))), Some(HashSet::from([1])));
+        assert_eq!(as_graph.group_asns(&ASGroup::ALL.difference(custom("a"))), Some(HashSet::from([3, 4])));
+        assert_eq!(as_graph.group_asns(&ASGroup::INTERSECTION(Vec::new())), Some(HashSet::new()));
+    }
+
+    #[test]
+    fn unloaded_custom_groups_are_none() {
+        let as_graph = as_graph();
+        assert_eq!(as_graph.group_asns(&custom("a")), None);
+        assert_eq!(as_graph.group_asns(&ASGroup::ALL.union(custom("a"))), None);
+        assert_eq!(as_graph.group_asns(&ASGroup::ALL.difference(custom("a"))), None);
+    }
+
+    #[test]
+    fn loading_errors() {
+        let mut as_graph = as_graph();
+        assert!(load(&mut as_graph, r#"{"stubs": [1]}"#).is_err());
+        assert!(load(&mut as_graph, r#"{"a": [1"#).is_err());
+        assert!(load(&mut as_graph, r#"{"a": ["x"]}"#).is_err());
+        assert!(as_graph.load_as_groups(Path::new("does/not/exist.json")).is_err());
+        // Nothing was loaded, and the built-in group wasn't replaced
+        assert_eq!(as_graph.group_asns(&custom("a")), None);
+        assert_eq!(as_graph.group_asns(&ASGroup::STUBS), Some(HashSet::from([1, 4])));
+    }
+
+    #[test]
+    fn names_parse_back_to_groups() {
+        for group in ASGroup::BUILT_IN {
+            assert_eq!(group.to_string().parse::<ASGroup>().unwrap(), group);
+        }
+        assert_eq!("rov_adopters".parse::<ASGroup>().unwrap(), custom("rov_adopters"));
+    }
+}
@@ -1,6 +1,11 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
 
-use crate::as_graphs::caida_as_graph::as_graph::{AutonomousSystem};
+use crate::simulation_engine::decision_process::DecisionProcess;
+use crate::simulation_engine::policies::Policy;
+use crate::simulation_engine::prefix::Prefix;
+use crate::simulation_engine::roa::{ROARouted, ROAValidity};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Relationships {
@@ -13,9 +18,12 @@ pub enum Relationships {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Announcement {
-    pub prefix: String,
+    pub prefix: Prefix,
     pub as_path: Vec<u32>,
     pub next_hop_asn: Option<u32>,
+    // The neighbor this copy was actually received from, set on receipt.
+    // Unlike as_path[0], senders can't forge it
+    pub recv_neighbor_asn: Option<u32>,
     pub seed_asn: Option<u32>,
     pub recv_relationship: Relationships,
     pub timestamp: u64,
@@ -24,17 +32,23 @@ pub struct Announcement {
     pub bgpsec_as_path: Vec<u32>,
     pub only_to_customers: Option<u32>,
     pub rovpp_blackhole: bool,
+    pub roa_validity: ROAValidity,
+    pub roa_routed: ROARouted,
 }
 
 impl Announcement {
-    pub fn new(prefix: String, as_path: Vec<u32>, recv_relationship: Relationships) -> Self {
-        let next_hop_asn = if as_path.len() == 1 {
+    pub fn new(prefix: Prefix, as_path: Vec<u32>, recv_relationship: Relationships) -> Self {
+        // Origin announcements are seeded at the first AS on the path,
+        // which may not be the origin itself (e.g. forged-origin hijacks)
+        let seeded = as_path.len() == 1 || recv_relationship == Relationships::ORIGIN;
+
+        let next_hop_asn = if seeded {
             Some(as_path[0])
         } else {
             None
         };
 
-        let seed_asn = if as_path.len() == 1 {
+        let seed_asn = if seeded {
             Some(as_path[0])
         } else {
             None
@@ -44,6 +58,7 @@ impl Announcement {
             prefix,
             as_path,
             next_hop_asn,
+            recv_neighbor_asn: None,
             seed_asn,
             recv_relationship,
             timestamp: 0,
@@ -52,6 +67,8 @@ impl Announcement {
             bgpsec_as_path: Vec::new(),
             only_to_customers: None,
             rovpp_blackhole: false,
+            roa_validity: ROAValidity::UNKNOWN,
+            roa_routed: ROARouted::UNKNOWN,
         }
     }
 
@@ -63,14 +80,26 @@ impl Announcement {
         self.bgpsec_next_asn == Some(asn) && self.bgpsec_as_path == self.as_path
     }
 
+    pub fn invalid_by_roa(&self) -> bool {
+        self.roa_validity.is_invalid()
+    }
+
+    pub fn valid_by_roa(&self) -> bool {
+        self.roa_validity == ROAValidity::VALID
+    }
+
+    pub fn unknown_by_roa(&self) -> bool {
+        self.roa_validity == ROAValidity::UNKNOWN
+    }
+
     pub fn origin(&self) -> u32 {
         *self.as_path.last().expect("AS path should not be empty")
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct LocalRIB {
-    pub data: HashMap<String, Announcement>,
+    pub data: HashMap<Prefix, Announcement>,
 }
 
 impl LocalRIB {
@@ -81,13 +110,21 @@ impl LocalRIB {
     }
 
     pub fn add_ann(&mut self, ann: Announcement) {
-        self.data.insert(ann.prefix.clone(), ann);
+        self.data.insert(ann.prefix, ann);
+    }
+
+    // The most specific announcement whose prefix contains addr
+    pub fn longest_prefix_match(&self, addr: &IpAddr) -> Option<&Announcement> {
+        self.data
+            .values()
+            .filter(|ann| ann.prefix.contains_addr(addr))
+            .max_by_key(|ann| ann.prefix.prefix_len())
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RecvQueue {
-    pub data: HashMap<String, Vec<Announcement>>,
+    pub data: HashMap<Prefix, Vec<Announcement>>,
 }
 
 impl RecvQueue {
@@ -98,183 +135,80 @@ impl RecvQueue {
     }
 
     pub fn add_ann(&mut self, ann: Announcement) {
-        self.data.entry(ann.prefix.clone()).or_default().push(ann);
+        self.data.entry(ann.prefix).or_default().push(ann);
     }
 
-    pub fn get_ann_list(&self, prefix: &str) -> Vec<Announcement> {
+    pub fn get_ann_list(&self, prefix: &Prefix) -> Vec<Announcement> {
         self.data.get(prefix).cloned().unwrap_or_default()
     }
 }
 
 
+// Plain BGP and the state every policy is built on.
+// The inherent hook methods here are the base behavior: Policy's default
+// methods delegate to them, and policies that override a hook can still call them.
 #[derive(Debug, Clone)]
 pub struct BGP {
     pub local_rib: LocalRIB,
     pub recv_q: RecvQueue,
-    pub autonomous_system: *const AutonomousSystem,
+    pub asn: u32,
+    // Shared by ASes configured alike (see SimulationEngine::set_decision_process)
+    pub decision_process: Arc<DecisionProcess>,
 }
 
 impl BGP {
-    pub fn new(autonomous_system: *const AutonomousSystem) -> Self {
+    pub fn new(asn: u32) -> Self {
         Self {
             local_rib: LocalRIB::new(),
             recv_q: RecvQueue::new(),
-            autonomous_system,
-        }
-    }
-
-    pub fn seed_ann(&mut self, ann: Announcement) {
-        assert!(self.local_rib.data.get(&ann.prefix).is_none(), "Seeding conflict");
-        self.local_rib.add_ann(ann);
-    }
-
-    pub fn receive_ann(&mut self, ann: Announcement) {
-        self.recv_q.add_ann(ann);
-    }
-
-    pub fn process_incoming_anns(&mut self, from_rel: Relationships, reset_q: bool) {
-        for (prefix, ann_list) in &self.recv_q.data {
-            let mut current_ann = self.local_rib.data.get(prefix);
-            let original_ann = current_ann;
-
-            if let Some(current) = current_ann {
-                if current.seed_asn.is_some() {
-                    continue;
-                }
-            }
-
-            for new_ann in ann_list {
-                if self.valid_ann(new_ann, from_rel.clone()) {
-                    let processed_ann = self.copy_and_process(new_ann, from_rel.clone());
-
-                    if current_ann.is_none() || self.is_better_ann(current_ann.unwrap(), &processed_ann) {
-                        self.local_rib.add_ann(processed_ann);
-                        current_ann = self.local_rib.data.get(prefix);
-                    }
-                }
-            }
-
-            if original_ann != current_ann {
-                assert!(current_ann.is_some(), "No announcement found in local_rib after processing");
-                if let Some(current) = current_ann {
-                    let autonomous_system = unsafe { &*self.autonomous_system };
-                    assert!(current.seed_asn.is_none() || current.seed_asn == Some(autonomous_system.asn), "Seed ASN is incorrect");
-                }
-            }
-        }
-
-        if reset_q {
-            self.reset_recv_q();
+            asn,
+            decision_process: DecisionProcess::shared_default(),
         }
     }
 
-    fn is_better_ann(&self, current: &Announcement, new: &Announcement) -> bool {
-        if current.recv_relationship != new.recv_relationship {
-            return current.recv_relationship < new.recv_relationship;
-        }
-
-        if current.as_path.len() != new.as_path.len() {
-            return current.as_path.len() > new.as_path.len();
-        }
-
-        current.next_hop_asn > new.next_hop_asn
+    // Without any security preference, i.e. the SECURITY step always ties
+    pub fn is_better_ann(&self, current: &Announcement, new: &Announcement) -> bool {
+        self.decision_process.is_better_ann(self.asn, current, new, |_| 0)
     }
 
     pub fn copy_and_process(&self, ann: &Announcement, recv_relationship: Relationships) -> Announcement {
-        let autonomous_system = unsafe { &*self.autonomous_system };
-        let mut new_as_path = vec![autonomous_system.asn];
+        let mut new_as_path = vec![self.asn];
         new_as_path.extend(&ann.as_path);
 
         Announcement {
-            prefix: ann.prefix.clone(),
             as_path: new_as_path,
-            next_hop_asn: Some(autonomous_system.asn),
             seed_asn: None,
             recv_relationship,
-            timestamp: ann.timestamp,
-            withdraw: ann.withdraw,
-            bgpsec_next_asn: ann.bgpsec_next_asn,
-            bgpsec_as_path: ann.bgpsec_as_path.clone(),
-            only_to_customers: ann.only_to_customers,
-            rovpp_blackhole: ann.rovpp_blackhole,
+            ..ann.clone()
         }
     }
 
-    pub fn propagate_to_providers(&self) {
-        let neighbors = unsafe { &(*self.autonomous_system).providers };
-        let send_rels: HashSet<Relationships> = [
-            Relationships::ORIGIN,
-            Relationships::CUSTOMERS,
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        self.propagate(neighbors, send_rels, Relationships::PROVIDERS);
-    }
-
-    pub fn propagate_to_customers(&self) {
-        let neighbors = unsafe { &(*self.autonomous_system).customers };
-        let send_rels: HashSet<Relationships> = [
-            Relationships::ORIGIN,
-            Relationships::CUSTOMERS,
-            Relationships::PEERS,
-            Relationships::PROVIDERS,
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        self.propagate(neighbors, send_rels, Relationships::CUSTOMERS);
+    // Withdrawals are never selected. One still removes the current route if it came from
+    // the same neighbor (see Policy::process_incoming_anns), but without Adj-RIBs-In
+    // there's no other route to fall back to until a neighbor resends it (see BGPFull)
+    pub fn valid_ann(&self, ann: &Announcement, _recv_relationship: Relationships) -> bool {
+        !ann.withdraw && !ann.as_path.contains(&self.asn) && !ann.as_path.contains(&0)
     }
 
-    pub fn propagate_to_peers(&self) {
-        let neighbors = unsafe { &(*self.autonomous_system).peers };
-        let send_rels: HashSet<Relationships> = [
-            Relationships::ORIGIN,
-            Relationships::CUSTOMERS,
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        self.propagate(neighbors, send_rels, Relationships::PEERS);
+    pub fn reset_recv_q(&mut self) {
+        self.recv_q = RecvQueue::new();
     }
+}
 
-    fn propagate(&self, neighbors: &Vec<*mut AutonomousSystem>, send_rels: HashSet<Relationships>, rel_type: Relationships) {
-        for (_prefix, unprocessed_ann) in &self.local_rib.data {
-            if send_rels.contains(&unprocessed_ann.recv_relationship) {
-                let ann = Announcement {
-                    prefix: unprocessed_ann.prefix.clone(),
-                    as_path: unprocessed_ann.as_path.clone(),
-                    next_hop_asn: Some(unsafe { &*self.autonomous_system }.asn),
-                    seed_asn: unprocessed_ann.seed_asn,
-                    recv_relationship: rel_type.clone(),
-                    timestamp: unprocessed_ann.timestamp,
-                    withdraw: unprocessed_ann.withdraw,
-                    bgpsec_next_asn: unprocessed_ann.bgpsec_next_asn,
-                    bgpsec_as_path: unprocessed_ann.bgpsec_as_path.clone(),
-                    only_to_customers: unprocessed_ann.only_to_customers,
-                    rovpp_blackhole: unprocessed_ann.rovpp_blackhole,
-                };
-
-                for &neighbor in neighbors {
-                    self.process_outgoing_ann(neighbor, &ann, rel_type.clone(), &send_rels);
-                }
-            }
-        }
+impl Policy for BGP {
+    fn name(&self) -> &'static str {
+        "BGP"
     }
 
-    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
-        let autonomous_system = unsafe { &*self.autonomous_system };
-        !ann.as_path.contains(&autonomous_system.asn) && !ann.as_path.contains(&0)
+    fn bgp(&self) -> &BGP {
+        self
     }
 
-    fn process_outgoing_ann(&self, neighbor: *mut AutonomousSystem, ann: &Announcement, _rel_type: Relationships, _send_rels: &HashSet<Relationships>) {
-        println!("Sending {:?} to neighbor ", ann);
+    fn bgp_mut(&mut self) -> &mut BGP {
+        self
     }
 
-    fn reset_recv_q(&mut self) {
-        self.recv_q = RecvQueue::new();
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
     }
 }
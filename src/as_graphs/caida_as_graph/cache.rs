@@ -0,0 +1,143 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+// Bump whenever anything serialized into a cache changes, whether it's a type's layout
+// or how its contents are computed (e.g. propagation ranks or AS group membership),
+// so that caches written by older builds get rebuilt instead of loaded
+pub const CACHE_VERSION: u32 = 2;
+
+const MAGIC: [u8; 4] = *b"BGPr";
+// Bounds what reading the header of a foreign file can allocate
+const MAX_HEADER_BYTES: u64 = 4096;
+
+// Written ahead of the cached value. key says what the value is and how it
+// was built, e.g. which CycleHandling built an ASGraph
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CacheHeader {
+    magic: [u8; 4],
+    version: u32,
+    key: String,
+}
+
+impl CacheHeader {
+    fn new(key: &str) -> Self {
+        Self {
+            magic: MAGIC,
+            version: CACHE_VERSION,
+            key: key.to_string(),
+        }
+    }
+}
+
+// Writes value to path in bincode, going through a temporary file in the same
+// directory so that an interrupted write never leaves a truncated cache behind
+pub fn write_cache<T: Serialize>(path: &Path, key: &str, value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let tmp_file = NamedTempFile::new_in(dir)?;
+    {
+        let mut writer = BufWriter::new(tmp_file.as_file());
+        bincode::DefaultOptions::new().serialize_into(&mut writer, &CacheHeader::new(key))?;
+        bincode::DefaultOptions::new().serialize_into(&mut writer, value)?;
+        writer.flush()?;
+    }
+    tmp_file.persist(path)?;
+    Ok(())
+}
+
+// The value cached at path, or None if there's no cache, or it's from another
+// CACHE_VERSION, has a different key, or can't be read
+pub fn read_cache<T: DeserializeOwned>(path: &Path, key: &str) -> Option<T> {
+    let file = File::open(path).ok()?;
+    let mut reader = BufReader::new(file);
+
+    let header_options = bincode::DefaultOptions::new().with_limit(MAX_HEADER_BYTES);
+    match header_options.deserialize_from::<_, CacheHeader>(&mut reader) {
+        Ok(header) if header == CacheHeader::new(key) => {}
+        Ok(header) => {
+            log::info!(
+                "Ignoring stale cache {:?} (version {}, {:?}), expected version {}, {:?}",
+                path, header.version, header.key, CACHE_VERSION, key
+            );
+            return None;
+        }
+        Err(e) => {
+            log::warn!("Ignoring unreadable cache {:?}: {}", path, e);
+            return None;
+        }
+    }
+
+    match bincode::DefaultOptions::new().deserialize_from(&mut reader) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("Ignoring unreadable cache {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::as_graphs::caida_as_graph::CycleHandling;
+
+    fn key(cycle_handling: CycleHandling) -> String {
+        format!("ASGraph {:?}", cycle_handling)
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cache.bin");
+        let value = vec![(1u32, "a".to_string()), (2, "b".to_string())];
+        write_cache(&path, &key(CycleHandling::ERROR), &value).unwrap();
+        assert_eq!(read_cache::<Vec<(u32, String)>>(&path, &key(CycleHandling::ERROR)), Some(value));
+    }
+
+    #[test]
+    fn stale_caches_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cache.bin");
+
+        // Built with a different CycleHandling
+        write_cache(&path, &key(CycleHandling::ERROR), &1u32).unwrap();
+        assert_eq!(read_cache::<u32>(&path, &key(CycleHandling::BREAK_INTO_PEERS)), None);
+
+        // Written by a build with another CACHE_VERSION
+        let header = CacheHeader {
+            version: CACHE_VERSION + 1,
+            ..CacheHeader::new(&key(CycleHandling::ERROR))
+        };
+        let mut bytes = bincode::DefaultOptions::new().serialize(&header).unwrap();
+        bytes.extend(bincode::DefaultOptions::new().serialize(&1u32).unwrap());
+        fs::write(&path, bytes).unwrap();
+        assert_eq!(read_cache::<u32>(&path, &key(CycleHandling::ERROR)), None);
+    }
+
+    #[test]
+    fn unreadable_caches_are_ignored() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cache.bin");
+        assert_eq!(read_cache::<u32>(&path, "key"), None);
+
+        write_cache(&path, "key", &vec![1u32; 100]).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(read_cache::<Vec<u32>>(&path, "key"), None);
+        // Truncated within the header
+        fs::write(&path, &bytes[..6]).unwrap();
+        assert_eq!(read_cache::<Vec<u32>>(&path, "key"), None);
+
+        fs::write(&path, "not a cache").unwrap();
+        assert_eq!(read_cache::<Vec<u32>>(&path, "key"), None);
+    }
+}
@@ -2,10 +2,13 @@ use std::collections::{HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
-use crate::as_graphs::base::as_graph_collector::{BaseASGraphCollector, ASGraphCollector};
+use serde::{Deserialize, Serialize};
+use crate::as_graphs::base::as_graph_collector::{ASGraphCollector, BaseASGraphCollector};
 use crate::as_graphs::CAIDAASGraphCollector;
+use crate::as_graphs::caida_as_graph::{ASGraph, CycleHandling};
+use crate::as_graphs::caida_as_graph::cache::{read_cache, write_cache};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ASGraphInfo {
     pub customer_provider_links: HashSet<CustomerProviderLink>,
     pub peer_links: HashSet<PeerLink>,
@@ -29,13 +32,13 @@ impl ASGraphInfo {
     }
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CustomerProviderLink {
     pub customer_asn: u32,
     pub provider_asn: u32,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PeerLink {
     pub peer1_asn: u32,
     pub peer2_asn: u32,
@@ -50,12 +53,43 @@ impl CAIDAASGraphConstructor {
         Self { collector }
     }
 
+    // Parsed once per collector cache, then loaded from a binary cache next to it.
+    // The binary caches are only an optimization, so failing to write one is just logged
     pub fn run(&self) -> Result<ASGraphInfo, Box<dyn std::error::Error>> {
+        let cache_path = self.cache_path("as_graph_info.bin");
+        if let Some(as_graph_info) = read_cache(&cache_path, "ASGraphInfo") {
+            return Ok(as_graph_info);
+        }
+
         let dl_path = self.collector.run();
         let as_graph_info = self.get_as_graph_info(&dl_path)?;
+        if let Err(e) = write_cache(&cache_path, "ASGraphInfo", &as_graph_info) {
+            log::warn!("Couldn't write cache {:?}: {}", cache_path, e);
+        }
         Ok(as_graph_info)
     }
 
+    // The fully built ASGraph, loaded from a binary cache next to the collector's
+    // when one was built with the same cycle_handling
+    pub fn build_as_graph(&self, cycle_handling: CycleHandling) -> Result<ASGraph, Box<dyn std::error::Error>> {
+        let cache_path = self.cache_path("as_graph.bin");
+        let key = format!("ASGraph {:?}", cycle_handling);
+        if let Some(as_graph) = read_cache(&cache_path, &key) {
+            return Ok(as_graph);
+        }
+
+        let as_graph = ASGraph::try_new(self.run()?, cycle_handling)?;
+        if let Err(e) = write_cache(&cache_path, &key, &as_graph) {
+            log::warn!("Couldn't write cache {:?}: {}", cache_path, e);
+        }
+        Ok(as_graph)
+    }
+
+    // e.g. CAIDAASGraphCollector_2024.01.01.as_graph.bin for CAIDAASGraphCollector_2024.01.01.txt
+    fn cache_path(&self, extension: &str) -> PathBuf {
+        self.collector.cache_path().with_extension(extension)
+    }
+
     fn get_as_graph_info(&self, dl_path: &PathBuf) -> Result<ASGraphInfo, Box<dyn std::error::Error>> {
         let file = File::open(dl_path)?;
         let reader = BufReader::new(file);
@@ -89,8 +123,8 @@ impl CAIDAASGraphConstructor {
     }
 
     fn extract_input_clique_asns(&self, line: &str, input_clique_asns: &mut HashSet<u32>) {
-        if let Some(asns) = line.split(':').last() {
-            for asn in asns.trim().split_whitespace() {
+        if let Some(asns) = line.split(':').next_back() {
+            for asn in asns.split_whitespace() {
                 if let Ok(asn) = asn.parse::<u32>() {
                     input_clique_asns.insert(asn);
                 }
@@ -99,8 +133,8 @@ impl CAIDAASGraphConstructor {
     }
 
     fn extract_ixp_asns(&self, line: &str, ixp_asns: &mut HashSet<u32>) {
-        if let Some(asns) = line.split(':').last() {
-            for asn in asns.trim().split_whitespace() {
+        if let Some(asns) = line.split(':').next_back() {
+            for asn in asns.split_whitespace() {
                 if let Ok(asn) = asn.parse::<u32>() {
                     ixp_asns.insert(asn);
                 }
@@ -133,3 +167,31 @@ impl CAIDAASGraphConstructor {
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::{TimeZone, Utc};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn unwritable_cache_is_not_fatal() {
+        let cache_dir = TempDir::new().unwrap();
+        let dl_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let collector = CAIDAASGraphCollector::new(Some(dl_time), Some(cache_dir.path().to_path_buf()));
+        fs::write(collector.cache_path(), "# input clique: 1 2\n1|2|0|bgp\n1|3|-1|bgp\n2|3|-1|bgp\n").unwrap();
+
+        // Directories where the binary caches should go can't be replaced by files
+        let constructor = CAIDAASGraphConstructor::new(collector);
+        fs::create_dir(constructor.cache_path("as_graph_info.bin")).unwrap();
+        fs::create_dir(constructor.cache_path("as_graph.bin")).unwrap();
+
+        let as_graph = constructor.build_as_graph(CycleHandling::ERROR).unwrap();
+        assert_eq!(as_graph.len(), 3);
+        assert_eq!(as_graph.get_propagation_ranks(), vec![vec![3], vec![1, 2]]);
+        assert!(constructor.cache_path("as_graph.bin").is_dir());
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt;
+
+use crate::as_graphs::caida_as_graph::{ASGraph, AutonomousSystem};
+
+// What part an AS plays in the customer-provider hierarchy. Every AS has exactly one role.
+// As in BGPy, peers count as much as providers: an AS without customers is a stub with
+// a single provider or peer, and multihomed with more than one.
+// MULTIHOMED is the same set the original is_multihomed picked out (no customers and more
+// than one provider or peer), since that already matched BGPy's definition. Only stubs
+// changed: any AS with a single neighbor used to be a stub, even when that neighbor was
+// a customer, so a provider with one customer was both a stub and transit
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ASRole {
+    // No customers and exactly one provider or peer
+    STUB,
+    // No customers and more than one provider or peer
+    MULTIHOMED,
+    // At least one customer, whatever else it's connected to
+    TRANSIT,
+    // No neighbors at all
+    ISOLATED,
+}
+
+impl ASRole {
+    // Edge ASes provide transit to no one
+    pub fn is_edge(self) -> bool {
+        self != ASRole::TRANSIT
+    }
+}
+
+// Why an AS has its role
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleExplanation {
+    pub asn: u32,
+    pub role: ASRole,
+    // The neighbors that decided the role, sorted: customers for TRANSIT,
+    // providers and peers for STUB and MULTIHOMED, and none for ISOLATED
+    pub deciding_asns: Vec<u32>,
+}
+
+impl fmt::Display for RoleExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.role {
+            ASRole::TRANSIT => "customers",
+            ASRole::STUB => "no customers and a single provider or peer",
+            ASRole::MULTIHOMED => "no customers and providers or peers",
+            ASRole::ISOLATED => return write!(f, "AS {} is {:?}: no neighbors", self.asn, self.role),
+        };
+        write!(f, "AS {} is {:?}: {} {:?}", self.asn, self.role, reason, self.deciding_asns)
+    }
+}
+
+impl AutonomousSystem {
+    pub fn role(&self) -> ASRole {
+        if !self.customers.is_empty() {
+            ASRole::TRANSIT
+        } else {
+            match self.providers.len() + self.peers.len() {
+                0 => ASRole::ISOLATED,
+                1 => ASRole::STUB,
+                _ => ASRole::MULTIHOMED,
+            }
+        }
+    }
+
+    pub fn is_stub(&self) -> bool {
+        self.role() == ASRole::STUB
+    }
+
+    pub fn is_multihomed(&self) -> bool {
+        self.role() == ASRole::MULTIHOMED
+    }
+
+    pub fn is_transit(&self) -> bool {
+        self.role() == ASRole::TRANSIT
+    }
+
+    pub fn is_edge(&self) -> bool {
+        self.role().is_edge()
+    }
+}
+
+impl ASGraph {
+    // None if asn isn't in the graph
+    pub fn explain_role(&self, asn: u32) -> Option<RoleExplanation> {
+        let as_obj = self.get(asn)?;
+        let role = as_obj.role();
+        let mut deciding_asns: Vec<u32> = match role {
+            ASRole::TRANSIT => as_obj.customers.iter().map(|&idx| self[idx].asn).collect(),
+            ASRole::STUB | ASRole::MULTIHOMED => as_obj
+                .providers
+                .iter()
+                .chain(&as_obj.peers)
+                .map(|&idx| self[idx].asn)
+                .collect(),
+            ASRole::ISOLATED => Vec::new(),
+        };
+        deciding_asns.sort_unstable();
+        Some(RoleExplanation { asn, role, deciding_asns })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, ASGroup, CustomerProviderLink, CycleHandling, PeerLink};
+
+    fn as_graph_info(customer_provider_links: &[(u32, u32)], peer_links: &[(u32, u32)]) -> ASGraphInfo {
+        ASGraphInfo {
+            customer_provider_links: customer_provider_links
+                .iter()
+                .map(|&(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: peer_links
+                .iter()
+                .map(|&(peer1_asn, peer2_asn)| PeerLink { peer1_asn, peer2_asn })
+                .collect(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        }
+    }
+
+    fn as_graph(customer_provider_links: &[(u32, u32)], peer_links: &[(u32, u32)]) -> ASGraph {
+        ASGraph::new(as_graph_info(customer_provider_links, peer_links))
+    }
+
+    fn role(as_graph: &ASGraph, asn: u32) -> ASRole {
+        as_graph.get(asn).unwrap().role()
+    }
+
+    #[test]
+    fn provider_with_a_single_customer_is_transit() {
+        let as_graph = as_graph(&[(2, 1)], &[]);
+        assert_eq!(role(&as_graph, 1), ASRole::TRANSIT);
+        assert_eq!(role(&as_graph, 2), ASRole::STUB);
+        assert!(!as_graph.get(1).unwrap().is_edge());
+    }
+
+    #[test]
+    fn peers_count_like_providers() {
+        // 3 has one provider and a peer, 4 has two providers,
+        // 5 only has a peer and 6 only has two peers
+        let as_graph = as_graph(&[(3, 1), (4, 1), (4, 2)], &[(3, 5), (6, 1), (6, 2)]);
+        assert_eq!(role(&as_graph, 3), ASRole::MULTIHOMED);
+        assert_eq!(role(&as_graph, 4), ASRole::MULTIHOMED);
+        assert_eq!(role(&as_graph, 5), ASRole::STUB);
+        assert_eq!(role(&as_graph, 6), ASRole::MULTIHOMED);
+        assert!(as_graph.get(5).unwrap().is_edge());
+    }
+
+    #[test]
+    fn ases_without_neighbors_are_isolated() {
+        // An AS that's its own provider loses its only link when the cycle is broken
+        let as_graph_info = as_graph_info(&[(2, 1), (9, 9)], &[]);
+        let as_graph = ASGraph::try_new(as_graph_info, CycleHandling::BREAK_INTO_PEERS).unwrap();
+        assert_eq!(role(&as_graph, 9), ASRole::ISOLATED);
+        assert!(as_graph.get(9).unwrap().is_edge());
+        assert!(!as_graph.group_asns(&ASGroup::STUBS_OR_MH).unwrap().contains(&9));
+        assert_eq!(as_graph.explain_role(9).unwrap().to_string(), "AS 9 is ISOLATED: no neighbors");
+    }
+
+    #[test]
+    fn groups_follow_roles() {
+        let as_graph = as_graph(&[(3, 1), (4, 1), (4, 2), (1, 6)], &[(3, 5), (1, 2)]);
+        let group = |group| as_graph.group_asns(&group).unwrap();
+        assert_eq!(group(ASGroup::STUBS), HashSet::from([5]));
+        assert_eq!(group(ASGroup::MULTIHOMED), HashSet::from([3, 4]));
+        assert_eq!(group(ASGroup::STUBS_OR_MH), HashSet::from([3, 4, 5]));
+        assert_eq!(group(ASGroup::TRANSIT), HashSet::from([1, 2, 6]));
+    }
+
+    #[test]
+    fn explanation_names_deciding_neighbors() {
+        let as_graph = as_graph(&[(4, 2), (4, 1), (5, 4)], &[(4, 3), (5, 3)]);
+        let explanation = as_graph.explain_role(4).unwrap();
+        assert_eq!(explanation.role, ASRole::TRANSIT);
+        assert_eq!(explanation.deciding_asns, vec![5]);
+        assert_eq!(explanation.to_string(), "AS 4 is TRANSIT: customers [5]");
+
+        let explanation = as_graph.explain_role(5).unwrap();
+        assert_eq!(explanation.role, ASRole::MULTIHOMED);
+        assert_eq!(explanation.deciding_asns, vec![3, 4]);
+        assert_eq!(explanation.to_string(), "AS 5 is MULTIHOMED: no customers and providers or peers [3, 4]");
+
+        assert_eq!(as_graph.explain_role(2).unwrap().to_string(), "AS 2 is TRANSIT: customers [4]");
+        assert_eq!(as_graph.explain_role(99), None);
+    }
+}
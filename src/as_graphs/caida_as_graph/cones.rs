@@ -0,0 +1,214 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::as_graphs::caida_as_graph::{AsIdx, ASGraph, AutonomousSystem};
+
+// Cone and reachability queries. Like CAIDA's AS-Rank, an AS is part of its own cones.
+// The ASN queries return None for ASNs that aren't in the graph
+impl ASGraph {
+    // The AS and every AS reachable by following customers transitively
+    pub fn customer_cone(&self, asn: u32) -> Option<HashSet<u32>> {
+        let idx = self.idx(asn)?;
+        Some(self.asns_of(&self.closure(&[idx], |as_obj| &as_obj.customers)))
+    }
+
+    // The AS and every AS reachable by following providers transitively
+    pub fn provider_cone(&self, asn: u32) -> Option<HashSet<u32>> {
+        let idx = self.idx(asn)?;
+        Some(self.asns_of(&self.closure(&[idx], |as_obj| &as_obj.providers)))
+    }
+
+    pub fn customer_cone_size(&self, asn: u32) -> Option<usize> {
+        let idx = self.idx(asn)?;
+        Some(self.closure(&[idx], |as_obj| &as_obj.customers).len())
+    }
+
+    pub fn provider_cone_size(&self, asn: u32) -> Option<usize> {
+        let idx = self.idx(asn)?;
+        Some(self.closure(&[idx], |as_obj| &as_obj.providers).len())
+    }
+
+    // Customer cone size of every AS. Takes one traversal per AS, so it's quadratic
+    // on deep graphs. top_customer_cones is cheaper when only the largest are needed
+    pub fn customer_cone_sizes(&self) -> HashMap<u32, usize> {
+        self.cone_sizes(self.iter().map(|as_obj| as_obj.idx), |as_obj| &as_obj.customers)
+    }
+
+    pub fn provider_cone_sizes(&self) -> HashMap<u32, usize> {
+        self.cone_sizes(self.iter().map(|as_obj| as_obj.idx), |as_obj| &as_obj.providers)
+    }
+
+    // ASNs ordered like CAIDA's AS-Rank: largest customer cone first, ties by lowest ASN
+    pub fn as_rank_order(&self) -> Vec<u32> {
+        let cone_sizes = self.customer_cone_sizes();
+        let mut asns: Vec<u32> = self.asns().collect();
+        asns.sort_unstable_by_key(|asn| (std::cmp::Reverse(cone_sizes[asn]), *asn));
+        asns
+    }
+
+    // The first k ASNs of as_rank_order, without computing every customer cone.
+    // A provider's customer cone is strictly larger than its customers', so an AS
+    // with k or more ASes above it can't be among the k largest
+    pub fn top_customer_cones(&self, k: usize) -> Vec<u32> {
+        let candidates: Vec<AsIdx> = self
+            .iter()
+            .filter(|as_obj| self.has_fewer_ancestors(as_obj.idx, k))
+            .map(|as_obj| as_obj.idx)
+            .collect();
+        let cone_sizes = self.cone_sizes(candidates.into_iter(), |as_obj| &as_obj.customers);
+
+        let mut asns: Vec<u32> = cone_sizes.keys().copied().collect();
+        asns.sort_unstable_by_key(|asn| (std::cmp::Reverse(cone_sizes[asn]), *asn));
+        asns.truncate(k);
+        asns
+    }
+
+    // Every AS that a route originated by asn can reach under Gao-Rexford export rules,
+    // including asn itself: up any number of providers, across at most one peer link,
+    // then down any number of customers
+    pub fn valley_free_reachable(&self, asn: u32) -> Option<HashSet<u32>> {
+        let idx = self.idx(asn)?;
+        let uphill = self.closure(&[idx], |as_obj| &as_obj.providers);
+
+        let mut summits = uphill.clone();
+        for &up_idx in &uphill {
+            summits.extend(&self[up_idx].peers);
+        }
+
+        Some(self.asns_of(&self.closure(&summits, |as_obj| &as_obj.customers)))
+    }
+
+    // start and every AS reachable from it through next, without duplicates
+    fn closure<'a>(&'a self, start: &[AsIdx], next: impl Fn(&'a AutonomousSystem) -> &'a [AsIdx]) -> Vec<AsIdx> {
+        let mut visited = vec![false; self.len()];
+        let mut reached = Vec::new();
+        let mut stack: Vec<AsIdx> = start.to_vec();
+        while let Some(idx) = stack.pop() {
+            if std::mem::replace(&mut visited[idx.index()], true) {
+                continue;
+            }
+            reached.push(idx);
+            stack.extend(next(&self[idx]).iter().filter(|next_idx| !visited[next_idx.index()]));
+        }
+        reached
+    }
+
+    // Whether fewer than k ASes are reachable from idx through providers, not counting
+    // idx itself. Stops as soon as it finds k of them
+    fn has_fewer_ancestors(&self, idx: AsIdx, k: usize) -> bool {
+        let mut ancestors = HashSet::new();
+        let mut stack = vec![idx];
+        while let Some(idx) = stack.pop() {
+            for &provider_idx in &self[idx].providers {
+                if ancestors.insert(provider_idx) {
+                    if ancestors.len() >= k {
+                        return false;
+                    }
+                    stack.push(provider_idx);
+                }
+            }
+        }
+        true
+    }
+
+    // Cone size of each AS in idxs. Each traversal stamps the ASes it visits with its
+    // own number, so the visited buffer never needs clearing between ASes
+    fn cone_sizes<'a>(
+        &'a self,
+        idxs: impl Iterator<Item = AsIdx>,
+        next: impl Fn(&'a AutonomousSystem) -> &'a [AsIdx],
+    ) -> HashMap<u32, usize> {
+        let mut visited_by = vec![usize::MAX; self.len()];
+        let mut stack = Vec::new();
+        let mut sizes = HashMap::new();
+
+        for (i, start_idx) in idxs.enumerate() {
+            let mut size = 0;
+            stack.push(start_idx);
+            while let Some(idx) = stack.pop() {
+                if visited_by[idx.index()] == i {
+                    continue;
+                }
+                visited_by[idx.index()] = i;
+                size += 1;
+                stack.extend(next(&self[idx]).iter().filter(|next_idx| visited_by[next_idx.index()] != i));
+            }
+            sizes.insert(self[start_idx].asn, size);
+        }
+        sizes
+    }
+
+    fn asns_of(&self, idxs: &[AsIdx]) -> HashSet<u32> {
+        idxs.iter().map(|&idx| self[idx].asn).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink, PeerLink};
+
+    // 1 and 2 peer, as do 2 and 8. 5 and 6 are customers of 3, under 1,
+    // and 6 is also a customer of 9. 7 is a customer of 4, under 2
+    fn fixture() -> ASGraph {
+        as_graph_with_peers(&[(3, 1), (4, 2), (5, 3), (6, 3), (6, 9), (7, 4)], &[(1, 2), (2, 8)])
+    }
+
+    fn as_graph(customer_provider_links: &[(u32, u32)]) -> ASGraph {
+        as_graph_with_peers(customer_provider_links, &[])
+    }
+
+    fn as_graph_with_peers(customer_provider_links: &[(u32, u32)], peer_links: &[(u32, u32)]) -> ASGraph {
+        ASGraph::new(ASGraphInfo {
+            customer_provider_links: customer_provider_links
+                .iter()
+                .map(|&(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: peer_links
+                .iter()
+                .map(|&(peer1_asn, peer2_asn)| PeerLink { peer1_asn, peer2_asn })
+                .collect(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        })
+    }
+
+    #[test]
+    fn top_customer_cones_match_as_rank_order() {
+        let as_graph = as_graph(&[(4, 3), (3, 2), (2, 1), (5, 1), (6, 3), (7, 8), (9, 8), (10, 8), (11, 9)]);
+        let as_rank_order = as_graph.as_rank_order();
+        for k in 0..=as_graph.len() + 1 {
+            let expected: Vec<u32> = as_rank_order.iter().take(k).copied().collect();
+            assert_eq!(as_graph.top_customer_cones(k), expected);
+        }
+    }
+
+    #[test]
+    fn cones_include_the_as_itself() {
+        let as_graph = fixture();
+        assert_eq!(as_graph.customer_cone(1), Some(HashSet::from([1, 3, 5, 6])));
+        assert_eq!(as_graph.customer_cone(9), Some(HashSet::from([9, 6])));
+        assert_eq!(as_graph.customer_cone(5), Some(HashSet::from([5])));
+        assert_eq!(as_graph.provider_cone(6), Some(HashSet::from([6, 3, 9, 1])));
+        // Peers are in neither cone
+        assert_eq!(as_graph.provider_cone(8), Some(HashSet::from([8])));
+        assert_eq!(as_graph.customer_cone(99), None);
+
+        let customer_cone_sizes = as_graph.customer_cone_sizes();
+        assert_eq!(customer_cone_sizes[&1], 4);
+        assert_eq!(customer_cone_sizes[&2], 3);
+        assert_eq!(as_graph.customer_cone_size(3), Some(3));
+        assert_eq!(as_graph.provider_cone_sizes()[&7], 3);
+        assert_eq!(as_graph.provider_cone_size(6), Some(4));
+    }
+
+    #[test]
+    fn valley_free_reachable_goes_up_across_and_down() {
+        let as_graph = fixture();
+        // 5 reaches 2's cone across the 1-2 peer link, but not 9, which would mean
+        // going down to 6 and back up, or 8, which is a second peer link away
+        assert_eq!(as_graph.valley_free_reachable(5), Some(HashSet::from([1, 2, 3, 4, 5, 6, 7])));
+        // 8 only has its peer 2's cone, not 2's peer 1
+        assert_eq!(as_graph.valley_free_reachable(8), Some(HashSet::from([2, 4, 7, 8])));
+        assert_eq!(as_graph.valley_free_reachable(99), None);
+    }
+}
@@ -1,8 +1,15 @@
 pub mod caida_as_graph_collector;
 pub mod caida_as_graph_constructor;
 pub mod as_graph;
+pub mod as_group;
+pub mod cache;
+pub mod classification;
+pub mod cones;
 pub mod bgp;
 
 pub use caida_as_graph_collector::{CAIDAASGraphCollector};
 pub use caida_as_graph_constructor::{CAIDAASGraphConstructor, ASGraphInfo, CustomerProviderLink, PeerLink};
-pub use as_graph::{AutonomousSystem, ASGraph};
+pub use as_graph::{AsIdx, AutonomousSystem, ASGraph, CustomerProviderCycleError, CycleHandling};
+pub use as_group::{ASGroup, NUM_TIER_1_CANDIDATES};
+pub use cache::CACHE_VERSION;
+pub use classification::{ASRole, RoleExplanation};
@@ -0,0 +1,3 @@
+pub mod as_graphs;
+pub mod simulation_engine;
+pub mod simulation_framework;
@@ -1,11 +1,59 @@
-mod as_graphs;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-use as_graphs::{CAIDAASGraphCollector,CAIDAASGraphConstructor, AutonomousSystem, ASGraph};
-use crate::as_graphs::{BaseASGraphCollector, ASGraphCollector};
+use bgpr::as_graphs::{CAIDAASGraphCollector, CAIDAASGraphConstructor, BaseASGraphCollector, CycleHandling};
+use bgpr::simulation_engine::policies::independent_policy;
+use bgpr::simulation_engine::{ASPA, OnlyToCustomers, ROV, ROVPP, ROVPPVersion};
+use bgpr::simulation_framework::{AccidentalRouteLeak, PrefixHijack, Scenario, ScenarioSpec, Simulation, SubprefixHijack};
 
 fn main() {
+    // Usage: bgpr [output_dir] [seed]
+    let args: Vec<String> = std::env::args().collect();
+    let output_dir = args
+        .get(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("results"));
+    let seed: u64 = args
+        .get(2)
+        .map(|seed| seed.parse().expect("Seed must be an unsigned integer"))
+        .unwrap_or(0);
+
     let collector = CAIDAASGraphCollector::new(None, None);
     let constructor = CAIDAASGraphConstructor::new(collector);
-    let as_graph_info = constructor.run().unwrap();
-    let as_graph = ASGraph::new(as_graph_info);
+    let as_graph = constructor.build_as_graph(CycleHandling::ERROR).unwrap();
+
+    let rov = independent_policy(|asn| Box::new(ROV::new(asn)));
+    let scenario_specs = vec![
+        ScenarioSpec::new(
+            "SubprefixHijack ROV",
+            Arc::new(|config| Box::new(SubprefixHijack::new(config)) as Box<dyn Scenario>),
+            rov.clone(),
+        ),
+        ScenarioSpec::new(
+            "SubprefixHijack ROV++V1",
+            Arc::new(|config| Box::new(SubprefixHijack::new(config)) as Box<dyn Scenario>),
+            independent_policy(|asn| Box::new(ROVPP::new(asn, ROVPPVersion::V1, false))),
+        ),
+        ScenarioSpec::new(
+            "PrefixHijack ROV",
+            Arc::new(|config| Box::new(PrefixHijack::new(config)) as Box<dyn Scenario>),
+            rov,
+        ),
+        AccidentalRouteLeak::spec("AccidentalRouteLeak ASPA", ASPA::policy_factory()),
+        AccidentalRouteLeak::spec(
+            "AccidentalRouteLeak OTC",
+            independent_policy(|asn| Box::new(OnlyToCustomers::new(asn))),
+        ),
+    ];
+
+    let mut simulation = Simulation::new(
+        as_graph,
+        scenario_specs,
+        vec![10.0, 30.0, 50.0, 70.0, 90.0],
+        10,
+        output_dir.clone(),
+    );
+    simulation.seed = seed;
+    simulation.run().unwrap();
+    println!("Wrote results to {:?}", output_dir);
 }
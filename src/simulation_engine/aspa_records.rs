@@ -0,0 +1,54 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::as_graphs::ASGraph;
+
+// Result of checking a single customer -> provider hop against the ASPA records
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HopCheck {
+    // The customer has a record that authorizes the provider
+    PROVIDER_PLUS,
+    // The customer has a record that doesn't authorize the provider
+    NOT_PROVIDER_PLUS,
+    // The customer has no record
+    NO_ATTESTATION,
+}
+
+// ASPA records: customer ASN -> every provider ASN it authorizes.
+// An empty set is an AS0 record, i.e. the customer has no providers at all
+#[derive(Debug, Clone, Default)]
+pub struct ASPARecords {
+    pub data: HashMap<u32, HashSet<u32>>,
+}
+
+impl ASPARecords {
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    // Every ASN in registering_asns publishes a record listing its providers in the graph
+    pub fn from_as_graph(as_graph: &ASGraph, registering_asns: &HashSet<u32>) -> Self {
+        let mut records = Self::new();
+        for as_obj in as_graph {
+            if registering_asns.contains(&as_obj.asn) {
+                let provider_asns = as_obj.providers.iter().map(|&idx| as_graph[idx].asn).collect();
+                records.add_record(as_obj.asn, provider_asns);
+            }
+        }
+        records
+    }
+
+    pub fn add_record(&mut self, customer_asn: u32, provider_asns: HashSet<u32>) {
+        self.data.insert(customer_asn, provider_asns);
+    }
+
+    pub fn hop_check(&self, customer_asn: u32, provider_asn: u32) -> HopCheck {
+        match self.data.get(&customer_asn) {
+            None => HopCheck::NO_ATTESTATION,
+            Some(provider_asns) if provider_asns.contains(&provider_asn) => HopCheck::PROVIDER_PLUS,
+            Some(_) => HopCheck::NOT_PROVIDER_PLUS,
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, Relationships};
+
+// A step of best path selection, compared in the order listed in DecisionProcess::steps
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecisionStep {
+    // Higher Policy::security_preference wins
+    SECURITY,
+    // Higher LOCAL_PREF wins
+    LOCAL_PREF,
+    // Shorter AS path wins
+    AS_PATH_LENGTH,
+}
+
+// How announcements that tie on every step are ordered
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TieBreak {
+    LOWEST_NEXT_HOP,
+    // A random but fixed order of neighbors per AS. The order only depends on the
+    // seed and the ASNs involved, so it's reproducible and stays consistent
+    // across every comparison (which routes need to converge). A Simulation
+    // replaces the seed with one drawn from each trial's RNG, so the order
+    // differs between trials but a run is still reproducible
+    SEEDED_RANDOM(u64),
+}
+
+// Best path selection for an AS. The default is Gao-Rexford:
+// customers over peers over providers, then shortest path, then lowest next hop
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionProcess {
+    pub steps: Vec<DecisionStep>,
+    // LOCAL_PREF for routes from a specific neighbor ASN
+    pub neighbor_local_prefs: HashMap<u32, u32>,
+    // LOCAL_PREF for routes from any other neighbor of the relationship
+    pub relationship_local_prefs: HashMap<Relationships, u32>,
+    pub tie_break: TieBreak,
+}
+
+impl Default for DecisionProcess {
+    fn default() -> Self {
+        Self {
+            steps: vec![DecisionStep::LOCAL_PREF, DecisionStep::AS_PATH_LENGTH],
+            neighbor_local_prefs: HashMap::new(),
+            relationship_local_prefs: HashMap::from([
+                (Relationships::PROVIDERS, 100),
+                (Relationships::PEERS, 200),
+                (Relationships::CUSTOMERS, 300),
+                (Relationships::ORIGIN, 400),
+            ]),
+            tie_break: TieBreak::LOWEST_NEXT_HOP,
+        }
+    }
+}
+
+impl DecisionProcess {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The default process, shared by every AS that doesn't get its own
+    pub fn shared_default() -> Arc<DecisionProcess> {
+        static DEFAULT: OnceLock<Arc<DecisionProcess>> = OnceLock::new();
+        DEFAULT.get_or_init(|| Arc::new(DecisionProcess::default())).clone()
+    }
+
+    // The default process with the steps in a different order (or some left out,
+    // e.g. without AS_PATH_LENGTH to ignore path length)
+    pub fn with_steps(steps: Vec<DecisionStep>) -> Self {
+        Self {
+            steps,
+            ..Self::default()
+        }
+    }
+
+    pub fn local_pref(&self, ann: &Announcement) -> u32 {
+        ann.recv_neighbor_asn
+            .and_then(|neighbor_asn| self.neighbor_local_prefs.get(&neighbor_asn))
+            .or_else(|| self.relationship_local_prefs.get(&ann.recv_relationship))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // asn is the AS choosing between the routes, and security_preference
+    // scores announcements for the SECURITY step
+    pub fn is_better_ann(
+        &self,
+        asn: u32,
+        current: &Announcement,
+        new: &Announcement,
+        security_preference: impl Fn(&Announcement) -> u32,
+    ) -> bool {
+        for step in &self.steps {
+            // Ordered so that Greater means new is better
+            let ordering = match step {
+                DecisionStep::SECURITY => security_preference(new).cmp(&security_preference(current)),
+                DecisionStep::LOCAL_PREF => self.local_pref(new).cmp(&self.local_pref(current)),
+                DecisionStep::AS_PATH_LENGTH => current.as_path.len().cmp(&new.as_path.len()),
+            };
+            if ordering != Ordering::Equal {
+                return ordering == Ordering::Greater;
+            }
+        }
+
+        match self.tie_break {
+            TieBreak::LOWEST_NEXT_HOP => current.next_hop_asn > new.next_hop_asn,
+            TieBreak::SEEDED_RANDOM(seed) => {
+                let rank = |ann: &Announcement| (tie_break_rank(seed, asn, ann.next_hop_asn), ann.next_hop_asn);
+                rank(current) > rank(new)
+            }
+        }
+    }
+}
+
+// SplitMix64 over the seed and both ASNs. Hand-rolled rather than std's hasher,
+// which doesn't promise the same output across Rust releases
+fn tie_break_rank(seed: u64, asn: u32, next_hop_asn: Option<u32>) -> u64 {
+    let next_hop_asn = next_hop_asn.map_or(u64::MAX, u64::from);
+    let mut z = seed ^ (u64::from(asn) << 32) ^ next_hop_asn;
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
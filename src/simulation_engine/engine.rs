@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::{AsIdx, AutonomousSystem};
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, LocalRIB, Relationships};
+use crate::simulation_engine::decision_process::{DecisionProcess, DecisionStep};
+use crate::simulation_engine::policies::Policy;
+use crate::simulation_engine::prefix::Prefix;
+use crate::simulation_engine::roa::ROASet;
+
+// Propagates announcements across an ASGraph following Gao-Rexford:
+// up the propagation ranks to providers, once across peers,
+// then back down the ranks to customers.
+//
+// The ASGraph itself is never mutated, so engines can share one. All routing
+// state lives in policies, which is indexed by AsIdx in parallel with the graph.
+pub struct SimulationEngine {
+    pub as_graph: Arc<ASGraph>,
+    pub policies: Vec<Box<dyn Policy>>,
+    // Used to set the RPKI state of announcements as they are seeded
+    pub roas: ROASet,
+}
+
+impl SimulationEngine {
+    // Every AS runs plain BGP
+    pub fn new(as_graph: Arc<ASGraph>) -> Self {
+        Self::with_policies(as_graph, |as_obj| Box::new(BGP::new(as_obj.asn)))
+    }
+
+    // policy_for is called once per AS, so each ASN can run a different policy type
+    pub fn with_policies<F>(as_graph: Arc<ASGraph>, policy_for: F) -> Self
+    where
+        F: FnMut(&AutonomousSystem) -> Box<dyn Policy>,
+    {
+        let policies = as_graph.iter().map(policy_for).collect();
+        Self {
+            as_graph,
+            policies,
+            roas: ROASet::default(),
+        }
+    }
+
+    pub fn policy(&self, asn: u32) -> Option<&dyn Policy> {
+        self.as_graph.idx(asn).map(|idx| self.policies[idx.index()].as_ref())
+    }
+
+    pub fn policy_mut(&mut self, asn: u32) -> Option<&mut (dyn Policy + 'static)> {
+        self.as_graph.idx(asn).map(|idx| self.policies[idx.index()].as_mut())
+    }
+
+    pub fn set_policy(&mut self, asn: u32, policy: Box<dyn Policy>) {
+        let idx = self
+            .as_graph
+            .idx(asn)
+            .unwrap_or_else(|| panic!("ASN {} is not in the AS graph", asn));
+        self.policies[idx.index()] = policy;
+    }
+
+    // Has every AS select routes with decision_process, with the steps its policy
+    // asks for (see Policy::decision_steps). ASes with the same steps share one Arc
+    pub fn set_decision_process(&mut self, decision_process: &DecisionProcess) {
+        let mut shared: HashMap<Vec<DecisionStep>, Arc<DecisionProcess>> = HashMap::new();
+        for policy in &mut self.policies {
+            let steps = policy.decision_steps(&decision_process.steps);
+            let policy_decision_process = shared
+                .entry(steps)
+                .or_insert_with_key(|steps| {
+                    Arc::new(DecisionProcess {
+                        steps: steps.clone(),
+                        ..decision_process.clone()
+                    })
+                })
+                .clone();
+            policy.bgp_mut().decision_process = policy_decision_process;
+        }
+    }
+
+    pub fn seed_anns(&mut self, anns: Vec<Announcement>) {
+        for mut ann in anns {
+            // Neither the prefix nor the origin change during propagation,
+            // so the RPKI state only needs to be computed once
+            (ann.roa_validity, ann.roa_routed) = self.roas.get_roa_outcome(&ann.prefix, ann.origin());
+
+            let seed_asn = ann.seed_asn.expect("Seeded announcements must have a seed_asn");
+            let policy = self
+                .policy_mut(seed_asn)
+                .unwrap_or_else(|| panic!("Seed ASN {} is not in the AS graph", seed_asn));
+            policy.seed_ann(ann);
+        }
+    }
+
+    // Has asn stop originating prefix, e.g. an attacker pulling its hijack between rounds
+    pub fn withdraw_seeded_ann(&mut self, asn: u32, prefix: &Prefix) {
+        self.policy_mut(asn)
+            .unwrap_or_else(|| panic!("ASN {} is not in the AS graph", asn))
+            .withdraw_seeded_ann(prefix);
+    }
+
+    // Every AS's LocalRIB, indexed by AsIdx. Comparing snapshots from
+    // before and after a propagation round detects convergence
+    pub fn local_rib_snapshot(&self) -> Vec<LocalRIB> {
+        self.policies.iter().map(|policy| policy.local_rib().clone()).collect()
+    }
+
+    // A single propagation round. Announcements only cross each link once per round,
+    // so policies that react to what they learned may need several rounds to converge
+    pub fn run(&mut self) {
+        let propagation_ranks: Vec<Vec<AsIdx>> = self
+            .as_graph
+            .get_propagation_ranks()
+            .into_iter()
+            .map(|rank| rank.into_iter().map(|asn| self.as_graph.idx(asn).unwrap()).collect())
+            .collect();
+
+        self.propagate_to_providers(&propagation_ranks);
+        self.propagate_to_peers();
+        self.propagate_to_customers(&propagation_ranks);
+    }
+
+    fn propagate_to_providers(&mut self, propagation_ranks: &[Vec<AsIdx>]) {
+        for (i, rank) in propagation_ranks.iter().enumerate() {
+            // Nothing has been sent to the lowest rank yet
+            if i > 0 {
+                for &idx in rank {
+                    self.policies[idx.index()].process_incoming_anns(Relationships::CUSTOMERS, true);
+                }
+            }
+            for &idx in rank {
+                self.send(idx, Relationships::PROVIDERS);
+            }
+        }
+    }
+
+    fn propagate_to_peers(&mut self) {
+        // Everyone sends before anyone processes so that
+        // announcements only travel across a single peer link
+        for i in 0..self.as_graph.len() {
+            self.send(AsIdx(i as u32), Relationships::PEERS);
+        }
+        for policy in &mut self.policies {
+            policy.process_incoming_anns(Relationships::PEERS, true);
+        }
+    }
+
+    fn propagate_to_customers(&mut self, propagation_ranks: &[Vec<AsIdx>]) {
+        for (i, rank) in propagation_ranks.iter().rev().enumerate() {
+            // Nothing has been sent to the highest rank yet
+            if i > 0 {
+                for &idx in rank {
+                    self.policies[idx.index()].process_incoming_anns(Relationships::PROVIDERS, true);
+                }
+            }
+            for &idx in rank {
+                self.send(idx, Relationships::CUSTOMERS);
+            }
+        }
+    }
+
+    // Has the AS at idx send to its neighbors of type to_rel, then delivers the results
+    fn send(&mut self, idx: AsIdx, to_rel: Relationships) {
+        let as_obj = &self.as_graph[idx];
+        let policy = &mut self.policies[idx.index()];
+        let mut outbox = Vec::new();
+        match to_rel {
+            Relationships::PROVIDERS => policy.propagate_to_providers(&self.as_graph, as_obj, &mut outbox),
+            Relationships::PEERS => policy.propagate_to_peers(&self.as_graph, as_obj, &mut outbox),
+            Relationships::CUSTOMERS => policy.propagate_to_customers(&self.as_graph, as_obj, &mut outbox),
+            _ => panic!("Can't propagate to {:?}", to_rel),
+        }
+
+        let sender_asn = self.as_graph[idx].asn;
+        for (neighbor, ann) in outbox {
+            self.policies[neighbor.index()].receive_ann(ann, sender_asn);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink, PeerLink};
+
+    fn prefix() -> Prefix {
+        "1.2.0.0/16".parse().unwrap()
+    }
+
+    // An engine running plain BGP everywhere, after one round with origin_asn announcing prefix()
+    fn engine(
+        customer_provider_links: &[(u32, u32)],
+        peer_links: &[(u32, u32)],
+        origin_asn: u32,
+    ) -> SimulationEngine {
+        let as_graph = ASGraph::new(ASGraphInfo {
+            customer_provider_links: customer_provider_links
+                .iter()
+                .map(|&(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: peer_links
+                .iter()
+                .map(|&(peer1_asn, peer2_asn)| PeerLink { peer1_asn, peer2_asn })
+                .collect(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        });
+        let mut engine = SimulationEngine::new(Arc::new(as_graph));
+        engine.seed_anns(vec![Announcement::new(prefix(), vec![origin_asn], Relationships::ORIGIN)]);
+        engine.run();
+        engine
+    }
+
+    fn route(engine: &SimulationEngine, asn: u32) -> Option<Vec<u32>> {
+        engine.policy(asn).unwrap().local_rib().data.get(&prefix()).map(|ann| ann.as_path.clone())
+    }
+
+    #[test]
+    fn routes_follow_gao_rexford_export_rules() {
+        // 3 originates. Its provider 1 peers with 2, which peers with 8 and has customers
+        // 4 and then 6. 3 also peers with 7 (customer of 9, provider of 10), and has
+        // customer 5, which is also a customer of 12 and peers with 11
+        let engine = engine(
+            &[(3, 1), (4, 2), (6, 4), (5, 3), (5, 12), (7, 9), (10, 7)],
+            &[(1, 2), (2, 8), (3, 7), (5, 11)],
+            3,
+        );
+
+        // Customer routes go to providers, peers and customers
+        assert_eq!(route(&engine, 1), Some(vec![1, 3]));
+        assert_eq!(route(&engine, 7), Some(vec![7, 3]));
+        assert_eq!(route(&engine, 5), Some(vec![5, 3]));
+        // Peer routes only go to customers, so they cross a single peer link
+        assert_eq!(route(&engine, 2), Some(vec![2, 1, 3]));
+        assert_eq!(route(&engine, 4), Some(vec![4, 2, 1, 3]));
+        assert_eq!(route(&engine, 6), Some(vec![6, 4, 2, 1, 3]));
+        assert_eq!(route(&engine, 10), Some(vec![10, 7, 3]));
+        assert_eq!(route(&engine, 8), None);
+        assert_eq!(route(&engine, 9), None);
+        // Provider routes only go to customers
+        assert_eq!(route(&engine, 12), None);
+        assert_eq!(route(&engine, 11), None);
+    }
+
+    #[test]
+    fn customers_beat_peers_beat_providers() {
+        // Origin 30 reaches 20 through customers 32 and 31, through peer 33, and through
+        // provider 25. The customer path is the longest and the provider has the lowest
+        // next hop, so only relationships can decide
+        let customer_links = [(30, 31), (31, 32), (32, 20)];
+        let peer_side_links = [(30, 33)];
+        let provider_links = [(30, 25), (20, 25)];
+        let peer_links = [(33, 20)];
+
+        let all_links: Vec<(u32, u32)> = [&customer_links[..], &peer_side_links, &provider_links].concat();
+        assert_eq!(route(&engine(&all_links, &peer_links, 30), 20), Some(vec![20, 32, 31, 30]));
+
+        let without_customers: Vec<(u32, u32)> = [&peer_side_links[..], &provider_links].concat();
+        assert_eq!(route(&engine(&without_customers, &peer_links, 30), 20), Some(vec![20, 33, 30]));
+
+        assert_eq!(route(&engine(&provider_links, &[], 30), 20), Some(vec![20, 25, 30]));
+    }
+}
@@ -0,0 +1,18 @@
+pub mod aspa_records;
+pub mod decision_process;
+pub mod engine;
+pub mod path_end_records;
+pub mod policies;
+pub mod prefix;
+pub mod roa;
+
+pub use aspa_records::{ASPARecords, HopCheck};
+pub use decision_process::{DecisionProcess, DecisionStep, TieBreak};
+pub use engine::SimulationEngine;
+pub use path_end_records::PathEndRecords;
+pub use policies::{
+    ASPA, BGPFull, BGPsec, BGPsecPreference, EdgeFilter, EnforceFirstAS, LeakBehavior, OnlyToCustomers, PathEnd, PeerLock,
+    Policy, ROV, ROVPP, ROVPPVersion, RouteLeaker,
+};
+pub use prefix::{Prefix, PrefixParseError};
+pub use roa::{ROA, ROARouted, ROASet, ROAValidity};
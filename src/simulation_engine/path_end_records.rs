@@ -0,0 +1,39 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::as_graphs::ASGraph;
+
+// Path-End records: origin ASN -> every neighbor ASN it authorizes as the last hop before it
+#[derive(Debug, Clone, Default)]
+pub struct PathEndRecords {
+    pub data: HashMap<u32, HashSet<u32>>,
+}
+
+impl PathEndRecords {
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    // Every ASN in registering_asns publishes a record listing all of its neighbors in the graph
+    pub fn from_as_graph(as_graph: &ASGraph, registering_asns: &HashSet<u32>) -> Self {
+        let mut records = Self::new();
+        for as_obj in as_graph {
+            if registering_asns.contains(&as_obj.asn) {
+                records.add_record(as_obj.asn, as_obj.neighbor_asns(as_graph).into_iter().collect());
+            }
+        }
+        records
+    }
+
+    pub fn add_record(&mut self, origin_asn: u32, neighbor_asns: HashSet<u32>) {
+        self.data.insert(origin_asn, neighbor_asns);
+    }
+
+    // Origins without a record can't be checked, so any last hop is accepted
+    pub fn valid_last_hop(&self, origin_asn: u32, last_hop_asn: u32) -> bool {
+        self.data
+            .get(&origin_asn)
+            .is_none_or(|neighbor_asns| neighbor_asns.contains(&last_hop_asn))
+    }
+}
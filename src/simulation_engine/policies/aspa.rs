@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::aspa_records::{ASPARecords, HopCheck};
+use crate::simulation_engine::policies::{Policy, PolicyFactory};
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ASPAValidity {
+    VALID,
+    UNKNOWN,
+    INVALID,
+}
+
+// ASPA path verification (draft-ietf-sidrops-aspa-verification):
+// BGP that drops announcements whose AS path is ASPA-invalid.
+// Routes from customers and peers get upstream verification,
+// routes from providers get downstream verification
+#[derive(Debug, Clone)]
+pub struct ASPA {
+    pub bgp: BGP,
+    pub records: Arc<ASPARecords>,
+}
+
+impl ASPA {
+    pub fn new(asn: u32, records: Arc<ASPARecords>) -> Self {
+        Self {
+            bgp: BGP::new(asn),
+            records,
+        }
+    }
+
+    // Every adopter registers a record of its providers and verifies against all of them
+    pub fn policy_factory() -> PolicyFactory {
+        Arc::new(|as_graph: &ASGraph, adopting_asns: &HashSet<u32>| {
+            let records = Arc::new(ASPARecords::from_as_graph(as_graph, adopting_asns));
+            Arc::new(move |asn| Box::new(ASPA::new(asn, records.clone())) as Box<dyn Policy>)
+        })
+    }
+
+    pub fn get_validity(&self, ann: &Announcement, recv_relationship: &Relationships) -> ASPAValidity {
+        // The draft numbers the path from the origin, so work on it reversed
+        let mut path: Vec<u32> = ann.as_path.iter().rev().copied().collect();
+        // Prepending never changes the validity
+        path.dedup();
+
+        match recv_relationship {
+            Relationships::PROVIDERS => self.downstream_validity(&path),
+            _ => self.upstream_validity(&path),
+        }
+    }
+
+    fn upstream_validity(&self, path: &[u32]) -> ASPAValidity {
+        let n = path.len();
+        if n <= 1 {
+            ASPAValidity::VALID
+        } else if self.max_up_ramp(path) < n {
+            ASPAValidity::INVALID
+        } else if self.min_up_ramp(path) < n {
+            ASPAValidity::UNKNOWN
+        } else {
+            ASPAValidity::VALID
+        }
+    }
+
+    fn downstream_validity(&self, path: &[u32]) -> ASPAValidity {
+        let n = path.len();
+        if n <= 2 {
+            ASPAValidity::VALID
+        } else if self.max_up_ramp(path) + self.max_down_ramp(path) < n {
+            ASPAValidity::INVALID
+        } else if self.min_up_ramp(path) + self.min_down_ramp(path) < n {
+            ASPAValidity::UNKNOWN
+        } else {
+            ASPAValidity::VALID
+        }
+    }
+
+    // Length of the longest prefix of the (origin first) path that could be going
+    // up customer -> provider hops, counting hops without attestations as up
+    fn max_up_ramp(&self, path: &[u32]) -> usize {
+        self.up_ramp(path, |hop| hop != HopCheck::NOT_PROVIDER_PLUS)
+    }
+
+    // Same as max_up_ramp, but only counting hops that are attested
+    fn min_up_ramp(&self, path: &[u32]) -> usize {
+        self.up_ramp(path, |hop| hop == HopCheck::PROVIDER_PLUS)
+    }
+
+    // Length of the longest suffix of the path that could be going down
+    // provider -> customer hops towards us, counting hops without attestations
+    fn max_down_ramp(&self, path: &[u32]) -> usize {
+        self.down_ramp(path, |hop| hop != HopCheck::NOT_PROVIDER_PLUS)
+    }
+
+    fn min_down_ramp(&self, path: &[u32]) -> usize {
+        self.down_ramp(path, |hop| hop == HopCheck::PROVIDER_PLUS)
+    }
+
+    fn up_ramp(&self, path: &[u32], counts: impl Fn(HopCheck) -> bool) -> usize {
+        for i in 0..path.len() - 1 {
+            if !counts(self.records.hop_check(path[i], path[i + 1])) {
+                return i + 1;
+            }
+        }
+        path.len()
+    }
+
+    fn down_ramp(&self, path: &[u32], counts: impl Fn(HopCheck) -> bool) -> usize {
+        for j in (0..path.len() - 1).rev() {
+            if !counts(self.records.hop_check(path[j + 1], path[j])) {
+                return path.len() - 1 - j;
+            }
+        }
+        path.len()
+    }
+}
+
+impl Policy for ASPA {
+    fn name(&self) -> &'static str {
+        "ASPA"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        self.get_validity(ann, &recv_relationship) != ASPAValidity::INVALID
+            && self.bgp.valid_ann(ann, recv_relationship)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation_framework::scenarios::scenario::prefix;
+
+    // 3's provider is 4, whose provider is 5. 5 has no providers (an AS0 record),
+    // and 6's provider is 5. Nothing else has a record
+    fn aspa() -> ASPA {
+        let mut records = ASPARecords::new();
+        records.add_record(3, HashSet::from([4]));
+        records.add_record(4, HashSet::from([5]));
+        records.add_record(5, HashSet::new());
+        records.add_record(6, HashSet::from([5]));
+        ASPA::new(1, Arc::new(records))
+    }
+
+    fn validity(as_path: Vec<u32>, recv_relationship: Relationships) -> (ASPAValidity, bool) {
+        let mut ann = Announcement::new(prefix(), as_path, recv_relationship.clone());
+        ann.recv_neighbor_asn = Some(ann.as_path[0]);
+        ann.seed_asn = None;
+        let aspa = aspa();
+        (aspa.get_validity(&ann, &recv_relationship), aspa.valid_ann(&ann, recv_relationship))
+    }
+
+    #[test]
+    fn upstream_paths_must_only_go_up() {
+        assert_eq!(validity(vec![5, 4, 3], Relationships::CUSTOMERS), (ASPAValidity::VALID, true));
+        // 5 has no providers, so 2 must have learned the route from it going down and leaked it
+        assert_eq!(validity(vec![2, 5, 4, 3], Relationships::CUSTOMERS), (ASPAValidity::INVALID, false));
+        // 9 has no record, so 9 -> 4 might be going up
+        assert_eq!(validity(vec![5, 4, 9], Relationships::PEERS), (ASPAValidity::UNKNOWN, true));
+    }
+
+    #[test]
+    fn downstream_paths_may_go_up_then_down() {
+        // Up from 3 to 5, then down to 6
+        assert_eq!(validity(vec![6, 5, 4, 3], Relationships::PROVIDERS), (ASPAValidity::VALID, true));
+        // 3 doesn't authorize 7, and 5 has no providers, so 7 -> 5 can't be going either way
+        assert_eq!(validity(vec![6, 5, 7, 3], Relationships::PROVIDERS), (ASPAValidity::INVALID, false));
+        // Neither 9 nor 8 have records, so the path might go up from 9 through 8 to 5
+        assert_eq!(validity(vec![6, 5, 8, 9], Relationships::PROVIDERS), (ASPAValidity::UNKNOWN, true));
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::AsIdx;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::Policy;
+use crate::simulation_engine::prefix::Prefix;
+
+// Adj-RIBs-In: the latest unprocessed announcement each neighbor sent for each prefix,
+// along with the relationship it was received on
+#[derive(Debug, Clone, Default)]
+pub struct RIBsIn {
+    pub data: HashMap<u32, HashMap<Prefix, (Announcement, Relationships)>>,
+}
+
+impl RIBsIn {
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    // Replaces whatever the neighbor previously sent for the prefix
+    pub fn add_unprocessed_ann(&mut self, neighbor_asn: u32, ann: Announcement, recv_relationship: Relationships) {
+        self.data
+            .entry(neighbor_asn)
+            .or_default()
+            .insert(ann.prefix, (ann, recv_relationship));
+    }
+
+    pub fn remove_entry(&mut self, neighbor_asn: u32, prefix: &Prefix) {
+        if let Some(prefix_anns) = self.data.get_mut(&neighbor_asn) {
+            prefix_anns.remove(prefix);
+        }
+    }
+
+    // Every neighbor's announcement for the prefix
+    pub fn get_ann_infos<'a>(&'a self, prefix: &'a Prefix) -> impl Iterator<Item = &'a (Announcement, Relationships)> {
+        self.data.values().filter_map(move |prefix_anns| prefix_anns.get(prefix))
+    }
+}
+
+// Adj-RIBs-Out: the announcement last sent to each neighbor for each prefix
+#[derive(Debug, Clone, Default)]
+pub struct RIBsOut {
+    pub data: HashMap<u32, HashMap<Prefix, Announcement>>,
+}
+
+impl RIBsOut {
+    pub fn new() -> Self {
+        Self {
+            data: HashMap::new(),
+        }
+    }
+
+    pub fn get_ann(&self, neighbor_asn: u32, prefix: &Prefix) -> Option<&Announcement> {
+        self.data.get(&neighbor_asn).and_then(|prefix_anns| prefix_anns.get(prefix))
+    }
+
+    pub fn add_ann(&mut self, neighbor_asn: u32, ann: Announcement) {
+        self.data.entry(neighbor_asn).or_default().insert(ann.prefix, ann);
+    }
+
+    pub fn remove_entry(&mut self, neighbor_asn: u32, prefix: &Prefix) {
+        if let Some(prefix_anns) = self.data.get_mut(&neighbor_asn) {
+            prefix_anns.remove(prefix);
+        }
+    }
+}
+
+// BGP with Adj-RIBs-In/Out and withdrawals.
+//
+// Every announcement received is kept per neighbor, and the best route is reselected
+// from all of them whenever anything for the prefix arrives, so losing a route
+// falls back to the next best one. Neighbors are only sent what changed since the
+// last announcement to them, with a withdrawal when there's nothing left to send.
+//
+// Withdrawals are only understood by BGPFull, so it should run at every AS
+// when modeling events like withdrawing a hijack mid-run
+#[derive(Debug, Clone)]
+pub struct BGPFull {
+    pub bgp: BGP,
+    pub ribs_in: RIBsIn,
+    pub ribs_out: RIBsOut,
+}
+
+impl BGPFull {
+    pub fn new(asn: u32) -> Self {
+        Self {
+            bgp: BGP::new(asn),
+            ribs_in: RIBsIn::new(),
+            ribs_out: RIBsOut::new(),
+        }
+    }
+
+    // Reruns best path selection for prefix over the Adj-RIBs-In.
+    // Seeded announcements are never replaced
+    fn select_best_ann(&mut self, prefix: &Prefix) {
+        if self.local_rib().data.get(prefix).is_some_and(|ann| ann.seed_asn.is_some()) {
+            return;
+        }
+
+        let mut best: Option<Announcement> = None;
+        for (ann, recv_relationship) in self.ribs_in.get_ann_infos(prefix) {
+            if self.valid_ann(ann, recv_relationship.clone()) {
+                let processed = self.copy_and_process(ann, recv_relationship.clone());
+                if best.as_ref().is_none_or(|current| self.is_better_ann(current, &processed)) {
+                    best = Some(processed);
+                }
+            }
+        }
+
+        match best {
+            Some(ann) => self.bgp.local_rib.add_ann(ann),
+            None => {
+                self.bgp.local_rib.data.remove(prefix);
+            }
+        }
+    }
+}
+
+impl Policy for BGPFull {
+    fn name(&self) -> &'static str {
+        "BGPFull"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    // Falls back to the best route learned from a neighbor, if any
+    fn withdraw_seeded_ann(&mut self, prefix: &Prefix) {
+        if self.local_rib().data.get(prefix).is_some_and(|ann| ann.seed_asn.is_some()) {
+            self.bgp.local_rib.data.remove(prefix);
+            self.select_best_ann(prefix);
+        }
+    }
+
+    fn process_incoming_anns(&mut self, from_rel: Relationships, reset_q: bool) {
+        let recv_q = std::mem::take(&mut self.bgp.recv_q.data);
+        for (prefix, ann_list) in &recv_q {
+            // Anns from a neighbor arrive in the order sent, so a withdrawal
+            // followed by a replacement leaves the replacement
+            for ann in ann_list {
+                let neighbor_asn = ann.recv_neighbor_asn.expect("Received announcements have a neighbor");
+                if ann.withdraw {
+                    self.ribs_in.remove_entry(neighbor_asn, prefix);
+                } else {
+                    self.ribs_in.add_unprocessed_ann(neighbor_asn, ann.clone(), from_rel.clone());
+                }
+            }
+            self.select_best_ann(prefix);
+        }
+
+        if !reset_q {
+            self.bgp.recv_q.data = recv_q;
+        }
+    }
+
+    // Diffs what each neighbor should have against the Adj-RIBs-Out, sending only
+    // changed routes and withdrawing routes that can no longer be exported
+    fn propagate(&mut self, as_graph: &ASGraph, neighbors: &[AsIdx], send_rels: HashSet<Relationships>, rel_type: Relationships, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        for &neighbor_idx in neighbors {
+            let neighbor = &as_graph[neighbor_idx];
+
+            let mut prefixes: HashSet<Prefix> = self.local_rib().data.keys().copied().collect();
+            if let Some(sent) = self.ribs_out.data.get(&neighbor.asn) {
+                prefixes.extend(sent.keys().copied());
+            }
+
+            for prefix in prefixes {
+                let ann = self
+                    .local_rib()
+                    .data
+                    .get(&prefix)
+                    .filter(|ann| send_rels.contains(&ann.recv_relationship))
+                    .map(|ann| Announcement {
+                        next_hop_asn: Some(self.asn()),
+                        recv_relationship: rel_type.clone(),
+                        ..ann.clone()
+                    });
+                let sent = self.ribs_out.get_ann(neighbor.asn, &prefix);
+                if ann.as_ref() == sent {
+                    continue;
+                }
+
+                match ann {
+                    // Implicitly withdraws anything sent before
+                    Some(ann) => {
+                        self.process_outgoing_ann(neighbor, &ann, rel_type.clone(), &send_rels, outbox);
+                        self.ribs_out.add_ann(neighbor.asn, ann);
+                    }
+                    None => {
+                        let withdrawal = Announcement {
+                            withdraw: true,
+                            ..sent.unwrap().clone()
+                        };
+                        outbox.push((neighbor_idx, withdrawal));
+                        self.ribs_out.remove_entry(neighbor.asn, &prefix);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink};
+    use crate::simulation_engine::SimulationEngine;
+    use crate::simulation_engine::policies::independent_policy;
+    use crate::simulation_framework::scenarios::scenario::prefix;
+    use crate::simulation_framework::scenarios::{PrefixHijack, Scenario, ScenarioConfig};
+
+    // Victim 5, attacker 2 and bystander 4 are customers of 3, which is a customer of 1.
+    // Every AS runs BGPFull
+    fn engine() -> SimulationEngine {
+        let as_graph = ASGraph::new(ASGraphInfo {
+            customer_provider_links: [(5, 3), (2, 3), (4, 3), (3, 1)]
+                .into_iter()
+                .map(|(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: HashSet::new(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        });
+        let mut config = ScenarioConfig::new(
+            HashSet::from([5]),
+            HashSet::from([2]),
+            HashSet::new(),
+            independent_policy(|asn| Box::new(BGPFull::new(asn))),
+        );
+        config.base_policy = Arc::new(|asn| Box::new(BGPFull::new(asn)));
+        let scenario = PrefixHijack::new(config);
+        let mut engine = scenario.setup_engine(Arc::new(as_graph));
+        assert!(scenario.run(&mut engine).is_some());
+        engine
+    }
+
+    fn route(engine: &SimulationEngine, asn: u32) -> Option<Vec<u32>> {
+        engine.policy(asn).unwrap().local_rib().data.get(&prefix()).map(|ann| ann.as_path.clone())
+    }
+
+    #[test]
+    fn withdrawn_routes_fall_back_to_the_next_best() {
+        let mut engine = engine();
+        // 3 breaks the tie between its customers towards the lower next hop, the attacker
+        assert_eq!(route(&engine, 3), Some(vec![3, 2]));
+        assert_eq!(route(&engine, 1), Some(vec![1, 3, 2]));
+
+        engine.withdraw_seeded_ann(2, &prefix());
+        engine.run();
+        engine.run();
+        // 3 still has the victim's route in its Adj-RIBs-In, and the attacker learns it back
+        assert_eq!(route(&engine, 3), Some(vec![3, 5]));
+        assert_eq!(route(&engine, 1), Some(vec![1, 3, 5]));
+        assert_eq!(route(&engine, 4), Some(vec![4, 3, 5]));
+        assert_eq!(route(&engine, 2), Some(vec![2, 3, 5]));
+    }
+
+    #[test]
+    fn withdrawals_propagate() {
+        let mut engine = engine();
+        engine.withdraw_seeded_ann(2, &prefix());
+        engine.withdraw_seeded_ann(5, &prefix());
+        engine.run();
+        engine.run();
+        for asn in [1, 2, 3, 4, 5] {
+            assert_eq!(route(&engine, asn), None, "AS {} kept a route", asn);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::AsIdx;
+use crate::as_graphs::caida_as_graph::as_graph::AutonomousSystem;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::decision_process::{DecisionProcess, DecisionStep};
+use crate::simulation_engine::policies::{Policy, PolicyFactory};
+
+// Where BGPsec validity sits in best path selection
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BGPsecPreference {
+    // Secure routes beat insecure ones regardless of relationship or path length
+    SECURITY_FIRST,
+    // Security only breaks ties between routes with the same relationship and path length
+    SECURITY_THIRD,
+}
+
+impl BGPsecPreference {
+    // steps with SECURITY moved to where this preference puts it
+    pub fn decision_steps(self, steps: &[DecisionStep]) -> Vec<DecisionStep> {
+        let mut steps: Vec<DecisionStep> =
+            steps.iter().copied().filter(|&step| step != DecisionStep::SECURITY).collect();
+        match self {
+            BGPsecPreference::SECURITY_FIRST => steps.insert(0, DecisionStep::SECURITY),
+            BGPsecPreference::SECURITY_THIRD => steps.push(DecisionStep::SECURITY),
+        }
+        steps
+    }
+
+    // The default process with this preference's steps, shared by every BGPsec AS using it
+    fn default_decision_process(self) -> Arc<DecisionProcess> {
+        static SECURITY_FIRST: OnceLock<Arc<DecisionProcess>> = OnceLock::new();
+        static SECURITY_THIRD: OnceLock<Arc<DecisionProcess>> = OnceLock::new();
+        let decision_process = match self {
+            BGPsecPreference::SECURITY_FIRST => &SECURITY_FIRST,
+            BGPsecPreference::SECURITY_THIRD => &SECURITY_THIRD,
+        };
+        decision_process
+            .get_or_init(|| {
+                let steps = self.decision_steps(&DecisionProcess::default().steps);
+                Arc::new(DecisionProcess::with_steps(steps))
+            })
+            .clone()
+    }
+}
+
+// BGPsec in partial deployment.
+// Paths are only signed towards neighbors that also run BGPsec, so a path stays
+// secure only while every AS on it adopts. Insecure routes are still accepted
+// and selected like plain BGP, using security as a preference rather than a filter
+#[derive(Debug, Clone)]
+pub struct BGPsec {
+    pub bgp: BGP,
+    pub preference: BGPsecPreference,
+    pub adopting_asns: Arc<HashSet<u32>>,
+}
+
+impl BGPsec {
+    pub fn new(asn: u32, preference: BGPsecPreference, adopting_asns: Arc<HashSet<u32>>) -> Self {
+        let mut bgp = BGP::new(asn);
+        bgp.decision_process = preference.default_decision_process();
+        Self {
+            bgp,
+            preference,
+            adopting_asns,
+        }
+    }
+
+    pub fn policy_factory(preference: BGPsecPreference) -> PolicyFactory {
+        Arc::new(move |_: &ASGraph, adopting_asns: &HashSet<u32>| {
+            let adopting_asns = Arc::new(adopting_asns.clone());
+            Arc::new(move |asn| Box::new(BGPsec::new(asn, preference, adopting_asns.clone())) as Box<dyn Policy>)
+        })
+    }
+
+    // For announcements already in (or about to enter) our local RIB,
+    // i.e. after copy_and_process has prepended our ASN to both paths
+    pub fn is_secure(ann: &Announcement) -> bool {
+        !ann.bgpsec_as_path.is_empty() && ann.bgpsec_as_path == ann.as_path
+    }
+}
+
+impl Policy for BGPsec {
+    fn name(&self) -> &'static str {
+        match self.preference {
+            BGPsecPreference::SECURITY_FIRST => "BGPsecSecurityFirst",
+            BGPsecPreference::SECURITY_THIRD => "BGPsec",
+        }
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    // The origin signs the path it originates
+    fn seed_ann(&mut self, ann: Announcement) {
+        let ann = Announcement {
+            bgpsec_as_path: ann.as_path.clone(),
+            ..ann
+        };
+        self.bgp.seed_ann(ann);
+    }
+
+    fn copy_and_process(&self, ann: &Announcement, recv_relationship: Relationships) -> Announcement {
+        let mut processed = self.bgp.copy_and_process(ann, recv_relationship);
+        // Only extend the signed path if it was signed for us and covers the whole AS path
+        processed.bgpsec_as_path = if ann.bgpsec_valid(self.bgp.asn) {
+            processed.as_path.clone()
+        } else {
+            Vec::new()
+        };
+        processed
+    }
+
+    fn decision_steps(&self, steps: &[DecisionStep]) -> Vec<DecisionStep> {
+        self.preference.decision_steps(steps)
+    }
+
+    fn security_preference(&self, ann: &Announcement) -> u32 {
+        Self::is_secure(ann) as u32
+    }
+
+    fn process_outgoing_ann(&self, neighbor: &AutonomousSystem, ann: &Announcement, _rel_type: Relationships, _send_rels: &HashSet<Relationships>, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        let ann = if self.adopting_asns.contains(&neighbor.asn) && Self::is_secure(ann) {
+            Announcement {
+                bgpsec_next_asn: Some(neighbor.asn),
+                ..ann.clone()
+            }
+        } else {
+            // Non-adopters can't verify signatures, so nothing is signed for them
+            Announcement {
+                bgpsec_next_asn: None,
+                bgpsec_as_path: Vec::new(),
+                ..ann.clone()
+            }
+        };
+        outbox.push((neighbor.idx, ann));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink};
+    use crate::simulation_framework::scenarios::scenario::prefix;
+    use crate::simulation_framework::scenarios::{PrefixHijack, Scenario, ScenarioConfig};
+
+    // Victim 5 reaches 1 through non-adopter 2, and through adopters 4 and 3
+    fn route_at_1(preference: BGPsecPreference) -> Announcement {
+        let as_graph = ASGraph::new(ASGraphInfo {
+            customer_provider_links: [(5, 2), (2, 1), (5, 4), (4, 3), (3, 1)]
+                .into_iter()
+                .map(|(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: HashSet::new(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        });
+        let config = ScenarioConfig::new(
+            HashSet::from([5]),
+            HashSet::new(),
+            HashSet::from([1, 3, 4]),
+            BGPsec::policy_factory(preference),
+        );
+        let scenario = PrefixHijack::new(config);
+        let mut engine = scenario.setup_engine(Arc::new(as_graph));
+        assert!(scenario.run(&mut engine).is_some());
+        engine.policy(1).unwrap().local_rib().data[&prefix()].clone()
+    }
+
+    #[test]
+    fn security_first_prefers_longer_secure_paths() {
+        let ann = route_at_1(BGPsecPreference::SECURITY_FIRST);
+        assert_eq!(ann.as_path, vec![1, 3, 4, 5]);
+        assert!(BGPsec::is_secure(&ann));
+    }
+
+    #[test]
+    fn security_third_prefers_shorter_insecure_paths() {
+        let ann = route_at_1(BGPsecPreference::SECURITY_THIRD);
+        assert_eq!(ann.as_path, vec![1, 2, 5]);
+        assert!(!BGPsec::is_secure(&ann));
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::{Policy, PolicyFactory};
+
+// AS path edge filter: BGP that drops announcements from edge neighbors (ASes
+// with no customers) unless the edge AS originated them itself, since an edge
+// AS should never be announcing anyone else's routes. Routes from transit
+// customers and peers must stay within that neighbor's customer cone, since
+// that's all they should be exporting. Needs no registration, so it protects
+// against leaks and hijacks from the first adopter
+#[derive(Debug, Clone)]
+pub struct EdgeFilter {
+    pub bgp: BGP,
+    pub edge_asns: Arc<HashSet<u32>>,
+    // Customer cones of the transit customers and peers of adopting ASes, by ASN
+    pub transit_cones: Arc<HashMap<u32, HashSet<u32>>>,
+}
+
+impl EdgeFilter {
+    pub fn new(asn: u32, edge_asns: Arc<HashSet<u32>>, transit_cones: Arc<HashMap<u32, HashSet<u32>>>) -> Self {
+        Self {
+            bgp: BGP::new(asn),
+            edge_asns,
+            transit_cones,
+        }
+    }
+
+    pub fn policy_factory() -> PolicyFactory {
+        Arc::new(|as_graph: &ASGraph, adopting_asns: &HashSet<u32>| {
+            let edge_asns: Arc<HashSet<u32>> = Arc::new(
+                as_graph
+                    .iter()
+                    .filter(|as_obj| as_obj.is_edge())
+                    .map(|as_obj| as_obj.asn)
+                    .collect(),
+            );
+
+            // Only the cones adopters will check against, each computed once
+            let mut transit_cones = HashMap::new();
+            for as_obj in adopting_asns.iter().filter_map(|&asn| as_graph.get(asn)) {
+                for &neighbor_idx in as_obj.customers.iter().chain(&as_obj.peers) {
+                    let neighbor = &as_graph[neighbor_idx];
+                    if neighbor.is_transit() && !transit_cones.contains_key(&neighbor.asn) {
+                        transit_cones.insert(neighbor.asn, as_graph.customer_cone(neighbor.asn).unwrap());
+                    }
+                }
+            }
+            let transit_cones = Arc::new(transit_cones);
+
+            Arc::new(move |asn| {
+                Box::new(EdgeFilter::new(asn, edge_asns.clone(), transit_cones.clone())) as Box<dyn Policy>
+            })
+        })
+    }
+
+    pub fn valid_edge_ann(&self, ann: &Announcement) -> bool {
+        match &ann.recv_neighbor_asn {
+            Some(neighbor_asn) if self.edge_asns.contains(neighbor_asn) => {
+                ann.as_path.iter().all(|asn| asn == neighbor_asn)
+            }
+            _ => true,
+        }
+    }
+
+    pub fn valid_cone_ann(&self, ann: &Announcement, recv_relationship: &Relationships) -> bool {
+        if !matches!(recv_relationship, Relationships::CUSTOMERS | Relationships::PEERS) {
+            return true;
+        }
+        match ann.recv_neighbor_asn.and_then(|neighbor_asn| self.transit_cones.get(&neighbor_asn)) {
+            Some(cone) => ann.as_path.iter().all(|asn| cone.contains(asn)),
+            None => true,
+        }
+    }
+}
+
+impl Policy for EdgeFilter {
+    fn name(&self) -> &'static str {
+        "EdgeFilter"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        self.valid_edge_ann(ann)
+            && self.valid_cone_ann(ann, &recv_relationship)
+            && self.bgp.valid_ann(ann, recv_relationship)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink, PeerLink};
+    use crate::simulation_framework::scenarios::scenario::prefix;
+
+    // Adopter 1 has transit customer 2 (with customer 3), stub customer 4, transit
+    // provider 6 and transit peer 7 (with customer 8). 5 is a peer of 2
+    fn edge_filter() -> Box<dyn Policy> {
+        let as_graph = ASGraph::new(ASGraphInfo {
+            customer_provider_links: [(2, 1), (3, 2), (4, 1), (1, 6), (8, 7)]
+                .into_iter()
+                .map(|(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: [(2, 5), (1, 7)]
+                .into_iter()
+                .map(|(peer1_asn, peer2_asn)| PeerLink { peer1_asn, peer2_asn })
+                .collect(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        });
+        EdgeFilter::policy_factory()(&as_graph, &HashSet::from([1]))(1)
+    }
+
+    fn valid(edge_filter: &dyn Policy, as_path: Vec<u32>, recv_relationship: Relationships) -> bool {
+        let mut ann = Announcement::new(prefix(), as_path, recv_relationship.clone());
+        ann.recv_neighbor_asn = Some(ann.as_path[0]);
+        ann.seed_asn = None;
+        edge_filter.valid_ann(&ann, recv_relationship)
+    }
+
+    #[test]
+    fn edge_neighbors_only_announce_themselves() {
+        let edge_filter = edge_filter();
+        assert!(valid(edge_filter.as_ref(), vec![4], Relationships::CUSTOMERS));
+        assert!(valid(edge_filter.as_ref(), vec![4, 4], Relationships::CUSTOMERS));
+        assert!(!valid(edge_filter.as_ref(), vec![4, 3], Relationships::CUSTOMERS));
+    }
+
+    #[test]
+    fn transit_customers_and_peers_stay_in_their_cones() {
+        let edge_filter = edge_filter();
+        assert!(valid(edge_filter.as_ref(), vec![2, 3], Relationships::CUSTOMERS));
+        // 5 is 2's peer, not its customer
+        assert!(!valid(edge_filter.as_ref(), vec![2, 5], Relationships::CUSTOMERS));
+        assert!(valid(edge_filter.as_ref(), vec![7, 8], Relationships::PEERS));
+        assert!(!valid(edge_filter.as_ref(), vec![7, 3], Relationships::PEERS));
+        // Providers may send anything
+        assert!(valid(edge_filter.as_ref(), vec![6, 7, 8], Relationships::PROVIDERS));
+    }
+}
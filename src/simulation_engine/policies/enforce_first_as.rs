@@ -0,0 +1,58 @@
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::Policy;
+
+// Enforce-first-AS: BGP that drops announcements whose AS path doesn't
+// start with the neighbor that actually sent them
+#[derive(Debug, Clone)]
+pub struct EnforceFirstAS {
+    pub bgp: BGP,
+}
+
+impl EnforceFirstAS {
+    pub fn new(asn: u32) -> Self {
+        Self { bgp: BGP::new(asn) }
+    }
+}
+
+impl Policy for EnforceFirstAS {
+    fn name(&self) -> &'static str {
+        "EnforceFirstAS"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        ann.as_path.first().is_some_and(|&first_asn| Some(first_asn) == ann.recv_neighbor_asn)
+            && self.bgp.valid_ann(ann, recv_relationship)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation_framework::scenarios::scenario::prefix;
+
+    fn valid(as_path: Vec<u32>, recv_neighbor_asn: u32) -> bool {
+        let mut ann = Announcement::new(prefix(), as_path, Relationships::CUSTOMERS);
+        ann.recv_neighbor_asn = Some(recv_neighbor_asn);
+        ann.seed_asn = None;
+        EnforceFirstAS::new(1).valid_ann(&ann, Relationships::CUSTOMERS)
+    }
+
+    #[test]
+    fn path_must_start_with_the_sender() {
+        assert!(valid(vec![2, 3], 2));
+        // 4 sent a path claiming to come from 2
+        assert!(!valid(vec![2, 3], 4));
+    }
+}
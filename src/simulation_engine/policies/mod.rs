@@ -0,0 +1,25 @@
+pub mod aspa;
+pub mod bgp_full;
+pub mod bgpsec;
+pub mod edge_filter;
+pub mod enforce_first_as;
+pub mod otc;
+pub mod path_end;
+pub mod peer_lock;
+pub mod policy;
+pub mod rov;
+pub mod route_leaker;
+pub mod rovpp;
+
+pub use aspa::{ASPA, ASPAValidity};
+pub use bgp_full::{BGPFull, RIBsIn, RIBsOut};
+pub use bgpsec::{BGPsec, BGPsecPreference};
+pub use edge_filter::EdgeFilter;
+pub use enforce_first_as::EnforceFirstAS;
+pub use otc::OnlyToCustomers;
+pub use path_end::PathEnd;
+pub use peer_lock::PeerLock;
+pub use policy::{independent_policy, Policy, PolicyConstructor, PolicyFactory};
+pub use rov::ROV;
+pub use route_leaker::{LeakBehavior, RouteLeaker};
+pub use rovpp::{ROVPP, ROVPPVersion};
@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::as_graphs::caida_as_graph::AsIdx;
+use crate::as_graphs::caida_as_graph::as_graph::AutonomousSystem;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::Policy;
+
+// Only-to-Customers attribute (RFC 9234).
+// Routes sent to customers and peers are marked with our ASN, and marked routes are
+// never exported to providers or peers. So any marked route from a customer has been
+// leaked, and so has a marked route from a peer unless that peer marked it itself
+#[derive(Debug, Clone)]
+pub struct OnlyToCustomers {
+    pub bgp: BGP,
+}
+
+impl OnlyToCustomers {
+    pub fn new(asn: u32) -> Self {
+        Self { bgp: BGP::new(asn) }
+    }
+}
+
+impl Policy for OnlyToCustomers {
+    fn name(&self) -> &'static str {
+        "OnlyToCustomers"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        let leaked = match (&recv_relationship, ann.only_to_customers) {
+            (Relationships::CUSTOMERS, Some(_)) => true,
+            (Relationships::PEERS, Some(otc_asn)) => Some(otc_asn) != ann.recv_neighbor_asn,
+            _ => false,
+        };
+        !leaked && self.bgp.valid_ann(ann, recv_relationship)
+    }
+
+    fn process_outgoing_ann(&self, neighbor: &AutonomousSystem, ann: &Announcement, rel_type: Relationships, _send_rels: &HashSet<Relationships>, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        match rel_type {
+            Relationships::PROVIDERS | Relationships::PEERS if ann.only_to_customers.is_some() => {}
+            Relationships::CUSTOMERS | Relationships::PEERS => {
+                let ann = Announcement {
+                    only_to_customers: ann.only_to_customers.or(Some(self.asn())),
+                    ..ann.clone()
+                };
+                outbox.push((neighbor.idx, ann));
+            }
+            _ => outbox.push((neighbor.idx, ann.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation_framework::scenarios::scenario::prefix;
+
+    fn valid(as_path: Vec<u32>, only_to_customers: Option<u32>, recv_relationship: Relationships) -> bool {
+        let mut ann = Announcement::new(prefix(), as_path, recv_relationship.clone());
+        ann.recv_neighbor_asn = Some(ann.as_path[0]);
+        ann.seed_asn = None;
+        ann.only_to_customers = only_to_customers;
+        OnlyToCustomers::new(1).valid_ann(&ann, recv_relationship)
+    }
+
+    #[test]
+    fn marked_routes_from_customers_are_leaks() {
+        assert!(valid(vec![2, 3], None, Relationships::CUSTOMERS));
+        assert!(!valid(vec![2, 3], Some(3), Relationships::CUSTOMERS));
+        // Even if the customer marked it itself
+        assert!(!valid(vec![2, 3], Some(2), Relationships::CUSTOMERS));
+    }
+
+    #[test]
+    fn peers_may_only_send_routes_they_marked() {
+        assert!(valid(vec![2, 3], None, Relationships::PEERS));
+        assert!(valid(vec![2, 3], Some(2), Relationships::PEERS));
+        assert!(!valid(vec![2, 3], Some(3), Relationships::PEERS));
+        // Providers send marked routes to their customers
+        assert!(valid(vec![2, 3], Some(3), Relationships::PROVIDERS));
+    }
+}
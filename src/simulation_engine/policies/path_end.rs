@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::path_end_records::PathEndRecords;
+use crate::simulation_engine::policies::{Policy, PolicyFactory};
+
+// Path-End validation: BGP that drops announcements whose last hop before the origin
+// isn't one of the origin's registered neighbors. Only the final link of the path
+// is checked, so it stops forged-origin hijacks but not longer forged paths
+#[derive(Debug, Clone)]
+pub struct PathEnd {
+    pub bgp: BGP,
+    pub records: Arc<PathEndRecords>,
+}
+
+impl PathEnd {
+    pub fn new(asn: u32, records: Arc<PathEndRecords>) -> Self {
+        Self {
+            bgp: BGP::new(asn),
+            records,
+        }
+    }
+
+    // Every adopter registers its neighbors and validates against all records
+    pub fn policy_factory() -> PolicyFactory {
+        Arc::new(|as_graph: &ASGraph, adopting_asns: &HashSet<u32>| {
+            let records = Arc::new(PathEndRecords::from_as_graph(as_graph, adopting_asns));
+            Arc::new(move |asn| Box::new(PathEnd::new(asn, records.clone())) as Box<dyn Policy>)
+        })
+    }
+
+    pub fn valid_path_end(&self, ann: &Announcement) -> bool {
+        match ann.as_path.as_slice() {
+            [.., last_hop_asn, origin_asn] => self.records.valid_last_hop(*origin_asn, *last_hop_asn),
+            _ => true,
+        }
+    }
+}
+
+impl Policy for PathEnd {
+    fn name(&self) -> &'static str {
+        "PathEnd"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        self.valid_path_end(ann) && self.bgp.valid_ann(ann, recv_relationship)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink};
+    use crate::simulation_engine::policies::independent_policy;
+    use crate::simulation_framework::scenarios::scenario::prefix;
+    use crate::simulation_framework::scenarios::{ForgedOriginPrefixHijack, Scenario, ScenarioConfig};
+
+    // Victim 5 is a customer of 7, which is a customer of 3, which is a customer
+    // of 1. Attacker 6 is also a customer of 1, so its forged path is shorter
+    fn route_at_1(adopt_policy: PolicyFactory) -> Vec<u32> {
+        let as_graph = ASGraph::new(ASGraphInfo {
+            customer_provider_links: [(5, 7), (7, 3), (3, 1), (6, 1)]
+                .into_iter()
+                .map(|(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: HashSet::new(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        });
+        let config =
+            ScenarioConfig::new(HashSet::from([5]), HashSet::from([6]), HashSet::from([1, 3, 7]), adopt_policy);
+        let scenario = ForgedOriginPrefixHijack::new(config);
+        let mut engine = scenario.setup_engine(Arc::new(as_graph));
+        assert!(scenario.run(&mut engine).is_some());
+        engine.policy(1).unwrap().local_rib().data[&prefix()].as_path.clone()
+    }
+
+    #[test]
+    fn adopters_reject_forged_origins() {
+        assert_eq!(route_at_1(independent_policy(|asn| Box::new(BGP::new(asn)))), vec![1, 6, 5]);
+        // The victim registers 7 as its only neighbor, so 6 can't be the last hop
+        assert_eq!(route_at_1(PathEnd::policy_factory()), vec![1, 3, 7, 5]);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::{Policy, PolicyFactory};
+
+// Peer Lock: BGP that protects tier-1 (input clique) ASNs from route leaks.
+//
+// The full version locks the tier-1s this AS peers with: any route from a customer or
+// peer containing one of them is dropped unless it came directly from that tier-1,
+// since the only other way to get such a route would be someone leaking it. Routes
+// from providers aren't checked, since they legitimately go through tier-1s.
+// The lite version needs no coordination with the tier-1s and locks all of them,
+// but only drops routes from customers, which never legitimately contain a tier-1
+#[derive(Debug, Clone)]
+pub struct PeerLock {
+    pub bgp: BGP,
+    pub lite: bool,
+    pub locked_asns: Arc<HashSet<u32>>,
+}
+
+impl PeerLock {
+    pub fn new(asn: u32, lite: bool, locked_asns: Arc<HashSet<u32>>) -> Self {
+        Self {
+            bgp: BGP::new(asn),
+            lite,
+            locked_asns,
+        }
+    }
+
+    pub fn policy_factory(lite: bool) -> PolicyFactory {
+        Arc::new(move |as_graph: &ASGraph, adopting_asns: &HashSet<u32>| {
+            let tier_1_asns: HashSet<u32> = as_graph
+                .iter()
+                .filter(|as_obj| as_obj.input_clique)
+                .map(|as_obj| as_obj.asn)
+                .collect();
+
+            let mut locked_asns: HashMap<u32, Arc<HashSet<u32>>> = HashMap::new();
+            if lite {
+                let tier_1_asns = Arc::new(tier_1_asns);
+                for &asn in adopting_asns {
+                    locked_asns.insert(asn, tier_1_asns.clone());
+                }
+            } else {
+                for &asn in adopting_asns {
+                    let as_obj = as_graph.get(asn).unwrap_or_else(|| panic!("Adopter {} is not in the AS graph", asn));
+                    let tier_1_peers = as_obj
+                        .peers
+                        .iter()
+                        .map(|&idx| as_graph[idx].asn)
+                        .filter(|peer_asn| tier_1_asns.contains(peer_asn))
+                        .collect();
+                    locked_asns.insert(asn, Arc::new(tier_1_peers));
+                }
+            }
+
+            Arc::new(move |asn| {
+                let locked_asns = locked_asns.get(&asn).cloned().unwrap_or_default();
+                Box::new(PeerLock::new(asn, lite, locked_asns)) as Box<dyn Policy>
+            })
+        })
+    }
+
+    pub fn valid_peer_lock_ann(&self, ann: &Announcement, recv_relationship: &Relationships) -> bool {
+        let checked_rels: &[Relationships] = if self.lite {
+            &[Relationships::CUSTOMERS]
+        } else {
+            &[Relationships::CUSTOMERS, Relationships::PEERS]
+        };
+        !checked_rels.contains(recv_relationship)
+            || ann
+                .as_path
+                .iter()
+                .all(|asn| !self.locked_asns.contains(asn) || Some(*asn) == ann.recv_neighbor_asn)
+    }
+}
+
+impl Policy for PeerLock {
+    fn name(&self) -> &'static str {
+        if self.lite {
+            "PeerLockLite"
+        } else {
+            "PeerLock"
+        }
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        self.valid_peer_lock_ann(ann, &recv_relationship) && self.bgp.valid_ann(ann, recv_relationship)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink, PeerLink};
+    use crate::simulation_framework::scenarios::scenario::prefix;
+
+    // Tier-1s 100 and 101 peer. Adopter 10 is a customer of 101, peers with 100
+    // and with non-tier-1 11, and has customer 20
+    fn peer_lock(lite: bool) -> Box<dyn Policy> {
+        let as_graph = ASGraph::new(ASGraphInfo {
+            customer_provider_links: [(10, 101), (20, 10)]
+                .into_iter()
+                .map(|(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: [(100, 101), (10, 100), (10, 11)]
+                .into_iter()
+                .map(|(peer1_asn, peer2_asn)| PeerLink { peer1_asn, peer2_asn })
+                .collect(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::from([100, 101]),
+        });
+        PeerLock::policy_factory(lite)(&as_graph, &HashSet::from([10]))(10)
+    }
+
+    fn valid(peer_lock: &dyn Policy, as_path: Vec<u32>, recv_relationship: Relationships) -> bool {
+        let mut ann = Announcement::new(prefix(), as_path, recv_relationship.clone());
+        ann.recv_neighbor_asn = Some(ann.as_path[0]);
+        ann.seed_asn = None;
+        peer_lock.valid_ann(&ann, recv_relationship)
+    }
+
+    #[test]
+    fn full_version_locks_tier_1_peers_on_customer_and_peer_sessions() {
+        let peer_lock = peer_lock(false);
+        assert!(valid(peer_lock.as_ref(), vec![100, 7], Relationships::PEERS));
+        assert!(!valid(peer_lock.as_ref(), vec![11, 100, 7], Relationships::PEERS));
+        assert!(!valid(peer_lock.as_ref(), vec![20, 100, 7], Relationships::CUSTOMERS));
+        assert!(valid(peer_lock.as_ref(), vec![20, 7], Relationships::CUSTOMERS));
+        // Providers route through tier-1s, and 101 isn't a peer so it isn't locked
+        assert!(valid(peer_lock.as_ref(), vec![101, 100, 7], Relationships::PROVIDERS));
+        assert!(valid(peer_lock.as_ref(), vec![20, 101, 7], Relationships::CUSTOMERS));
+    }
+
+    #[test]
+    fn lite_version_locks_every_tier_1_on_customer_sessions() {
+        let peer_lock = peer_lock(true);
+        assert!(!valid(peer_lock.as_ref(), vec![20, 101, 7], Relationships::CUSTOMERS));
+        assert!(valid(peer_lock.as_ref(), vec![11, 100, 7], Relationships::PEERS));
+        assert!(valid(peer_lock.as_ref(), vec![101, 100, 7], Relationships::PROVIDERS));
+    }
+}
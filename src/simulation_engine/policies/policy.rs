@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::AsIdx;
+use crate::as_graphs::caida_as_graph::as_graph::AutonomousSystem;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, LocalRIB, Relationships};
+use crate::simulation_engine::decision_process::DecisionStep;
+use crate::simulation_engine::prefix::Prefix;
+
+// A routing policy run by a single AS.
+//
+// Every default method is plain BGP, so a policy only needs to provide access
+// to its underlying BGP state and override the hooks it changes.
+// Overrides that want to extend BGP rather than replace it can call the
+// inherent methods on BGP (e.g. self.bgp().valid_ann(..)).
+pub trait Policy: std::fmt::Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn bgp(&self) -> &BGP;
+
+    fn bgp_mut(&mut self) -> &mut BGP;
+
+    fn clone_box(&self) -> Box<dyn Policy>;
+
+    fn asn(&self) -> u32 {
+        self.bgp().asn
+    }
+
+    fn local_rib(&self) -> &LocalRIB {
+        &self.bgp().local_rib
+    }
+
+    // Replaces any route learned for the prefix, since seeds can arrive between
+    // propagation rounds (e.g. a late hijack). Two seeds for one prefix conflict
+    fn seed_ann(&mut self, ann: Announcement) {
+        let local_rib = &mut self.bgp_mut().local_rib;
+        assert!(
+            local_rib.data.get(&ann.prefix).is_none_or(|current| current.seed_asn.is_none()),
+            "Seeding conflict"
+        );
+        local_rib.add_ann(ann);
+    }
+
+    // Stops originating prefix. Plain BGP keeps no Adj-RIBs-Out,
+    // so neighbors that already learned the route keep it
+    fn withdraw_seeded_ann(&mut self, prefix: &Prefix) {
+        let local_rib = &mut self.bgp_mut().local_rib;
+        if local_rib.data.get(prefix).is_some_and(|ann| ann.seed_asn.is_some()) {
+            local_rib.data.remove(prefix);
+        }
+    }
+
+    fn receive_ann(&mut self, ann: Announcement, neighbor_asn: u32) {
+        let ann = Announcement {
+            recv_neighbor_asn: Some(neighbor_asn),
+            ..ann
+        };
+        self.bgp_mut().recv_q.add_ann(ann);
+    }
+
+    fn process_incoming_anns(&mut self, from_rel: Relationships, reset_q: bool) {
+        let recv_q = std::mem::take(&mut self.bgp_mut().recv_q.data);
+        for (prefix, ann_list) in &recv_q {
+            let original_ann = self.local_rib().data.get(prefix).cloned();
+            let mut current_ann = original_ann.clone();
+
+            if let Some(current) = &current_ann {
+                if current.seed_asn.is_some() {
+                    continue;
+                }
+                // Whatever a neighbor sends for a prefix replaces what it sent before, so the
+                // current route is implicitly withdrawn once its neighbor sends again
+                let resent = ann_list
+                    .iter()
+                    .any(|ann| ann.recv_neighbor_asn.is_some() && ann.recv_neighbor_asn == current.recv_neighbor_asn);
+                if resent {
+                    current_ann = None;
+                }
+            }
+
+            for new_ann in ann_list {
+                if self.valid_ann(new_ann, from_rel.clone()) {
+                    let processed_ann = self.copy_and_process(new_ann, from_rel.clone());
+
+                    if current_ann.is_none() || self.is_better_ann(current_ann.as_ref().unwrap(), &processed_ann) {
+                        current_ann = Some(processed_ann);
+                    }
+                }
+            }
+
+            if original_ann != current_ann {
+                match current_ann {
+                    Some(current) => {
+                        assert!(current.seed_asn.is_none() || current.seed_asn == Some(self.asn()), "Seed ASN is incorrect");
+                        self.bgp_mut().local_rib.add_ann(current);
+                    }
+                    None => {
+                        self.bgp_mut().local_rib.data.remove(prefix);
+                    }
+                }
+            }
+        }
+
+        if reset_q {
+            self.bgp_mut().reset_recv_q();
+        } else {
+            self.bgp_mut().recv_q.data = recv_q;
+        }
+    }
+
+    fn is_better_ann(&self, current: &Announcement, new: &Announcement) -> bool {
+        let bgp = self.bgp();
+        bgp.decision_process
+            .is_better_ann(bgp.asn, current, new, |ann| self.security_preference(ann))
+    }
+
+    // The steps this policy selects routes with when the engine's DecisionProcess
+    // has the given ones, e.g. to add SECURITY. Plain BGP uses them as they are
+    fn decision_steps(&self, steps: &[DecisionStep]) -> Vec<DecisionStep> {
+        steps.to_vec()
+    }
+
+    // Score for the decision process's SECURITY step, higher is preferred.
+    // Plain BGP has no notion of security, so every announcement ties
+    fn security_preference(&self, _ann: &Announcement) -> u32 {
+        0
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        self.bgp().valid_ann(ann, recv_relationship)
+    }
+
+    fn copy_and_process(&self, ann: &Announcement, recv_relationship: Relationships) -> Announcement {
+        self.bgp().copy_and_process(ann, recv_relationship)
+    }
+
+    fn propagate_to_providers(&mut self, as_graph: &ASGraph, as_obj: &AutonomousSystem, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        let send_rels: HashSet<Relationships> = [
+            Relationships::ORIGIN,
+            Relationships::CUSTOMERS,
+        ]
+        .iter()
+        .cloned()
+        .collect();
+
+        self.propagate(as_graph, &as_obj.providers, send_rels, Relationships::PROVIDERS, outbox);
+    }
+
+    fn propagate_to_customers(&mut self, as_graph: &ASGraph, as_obj: &AutonomousSystem, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        let send_rels: HashSet<Relationships> = [
+            Relationships::ORIGIN,
+            Relationships::CUSTOMERS,
+            Relationships::PEERS,
+            Relationships::PROVIDERS,
+        ]
+        .iter()
+        .cloned()
+        .collect();
+
+        self.propagate(as_graph, &as_obj.customers, send_rels, Relationships::CUSTOMERS, outbox);
+    }
+
+    fn propagate_to_peers(&mut self, as_graph: &ASGraph, as_obj: &AutonomousSystem, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        let send_rels: HashSet<Relationships> = [
+            Relationships::ORIGIN,
+            Relationships::CUSTOMERS,
+        ]
+        .iter()
+        .cloned()
+        .collect();
+
+        self.propagate(as_graph, &as_obj.peers, send_rels, Relationships::PEERS, outbox);
+    }
+
+    fn propagate(&mut self, as_graph: &ASGraph, neighbors: &[AsIdx], send_rels: HashSet<Relationships>, rel_type: Relationships, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        for unprocessed_ann in self.local_rib().data.values() {
+            if send_rels.contains(&unprocessed_ann.recv_relationship) {
+                let ann = Announcement {
+                    next_hop_asn: Some(self.asn()),
+                    recv_relationship: rel_type.clone(),
+                    ..unprocessed_ann.clone()
+                };
+
+                for &neighbor in neighbors {
+                    self.process_outgoing_ann(&as_graph[neighbor], &ann, rel_type.clone(), &send_rels, outbox);
+                }
+            }
+        }
+    }
+
+    // The engine delivers the outbox to each neighbor's recv_q once this AS is done sending
+    fn process_outgoing_ann(&self, neighbor: &AutonomousSystem, ann: &Announcement, _rel_type: Relationships, _send_rels: &HashSet<Relationships>, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        outbox.push((neighbor.idx, ann.clone()));
+    }
+}
+
+// Builds the policy for a given ASN. Shared so that one constructor
+// can be handed to every adopting AS (and across threads)
+pub type PolicyConstructor = Arc<dyn Fn(u32) -> Box<dyn Policy> + Send + Sync>;
+
+// Builds the PolicyConstructor for a single engine once the AS graph and every
+// adopting ASN are known, so that adopters can share data derived from
+// who else adopts (e.g. ASPA records) instead of recomputing it per AS
+pub type PolicyFactory = Arc<dyn Fn(&ASGraph, &HashSet<u32>) -> PolicyConstructor + Send + Sync>;
+
+// For policies that behave the same no matter who else adopts
+pub fn independent_policy(constructor: fn(u32) -> Box<dyn Policy>) -> PolicyFactory {
+    Arc::new(move |_, _| Arc::new(constructor))
+}
+
+impl Clone for Box<dyn Policy> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
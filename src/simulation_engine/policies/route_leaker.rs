@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::AsIdx;
+use crate::as_graphs::caida_as_graph::as_graph::AutonomousSystem;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::Policy;
+
+// Which routes a RouteLeaker re-exports against valley-free rules, and to whom
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakBehavior {
+    // Relationships of the learned routes that get leaked (PROVIDERS and/or PEERS)
+    pub leaked_rels: HashSet<Relationships>,
+    pub leak_to_providers: bool,
+    pub leak_to_peers: bool,
+}
+
+// Leaks every provider and peer route to every provider and peer
+impl Default for LeakBehavior {
+    fn default() -> Self {
+        Self {
+            leaked_rels: HashSet::from([Relationships::PROVIDERS, Relationships::PEERS]),
+            leak_to_providers: true,
+            leak_to_peers: true,
+        }
+    }
+}
+
+// BGP that misconfigures its export filters: on top of its own and its customers'
+// routes, it also sends the routes in behavior.leaked_rels to providers and/or peers.
+// Routes are selected as usual, so a leak only goes out after the leaker has learned
+// the route, i.e. in the propagation round after the one it arrived in
+#[derive(Debug, Clone)]
+pub struct RouteLeaker {
+    pub bgp: BGP,
+    pub behavior: LeakBehavior,
+}
+
+impl RouteLeaker {
+    pub fn new(asn: u32, behavior: LeakBehavior) -> Self {
+        Self {
+            bgp: BGP::new(asn),
+            behavior,
+        }
+    }
+
+    fn export_rels(&self, leak: bool) -> HashSet<Relationships> {
+        let mut send_rels = HashSet::from([Relationships::ORIGIN, Relationships::CUSTOMERS]);
+        if leak {
+            send_rels.extend(self.behavior.leaked_rels.iter().cloned());
+        }
+        send_rels
+    }
+}
+
+impl Policy for RouteLeaker {
+    fn name(&self) -> &'static str {
+        "RouteLeaker"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn propagate_to_providers(&mut self, as_graph: &ASGraph, as_obj: &AutonomousSystem, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        let send_rels = self.export_rels(self.behavior.leak_to_providers);
+        self.propagate(as_graph, &as_obj.providers, send_rels, Relationships::PROVIDERS, outbox);
+    }
+
+    fn propagate_to_peers(&mut self, as_graph: &ASGraph, as_obj: &AutonomousSystem, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        let send_rels = self.export_rels(self.behavior.leak_to_peers);
+        self.propagate(as_graph, &as_obj.peers, send_rels, Relationships::PEERS, outbox);
+    }
+}
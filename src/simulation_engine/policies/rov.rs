@@ -0,0 +1,36 @@
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::Policy;
+
+// Route Origin Validation: BGP that drops RPKI-invalid announcements
+#[derive(Debug, Clone)]
+pub struct ROV {
+    pub bgp: BGP,
+}
+
+impl ROV {
+    pub fn new(asn: u32) -> Self {
+        Self { bgp: BGP::new(asn) }
+    }
+}
+
+impl Policy for ROV {
+    fn name(&self) -> &'static str {
+        "ROV"
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        !ann.invalid_by_roa() && self.bgp.valid_ann(ann, recv_relationship)
+    }
+}
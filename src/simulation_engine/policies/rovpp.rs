@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::as_graphs::caida_as_graph::AsIdx;
+use crate::as_graphs::caida_as_graph::as_graph::AutonomousSystem;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::policies::Policy;
+use crate::simulation_engine::prefix::Prefix;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ROVPPVersion {
+    // Blackholes hijacked subprefixes, but never announces the blackholes
+    V1,
+    // V1, but also announces blackholes to customers so that they don't
+    // fall back to the hijacked route
+    V2,
+    // V2, but prefers routing the subprefix through a neighbor that
+    // didn't send the hijack (a preventive announcement) over blackholing it
+    V3,
+}
+
+// ROV++ (Morillo et al., NDSS 2021): ROV that also protects against subprefix hijacks.
+//
+// After ROV drops an invalid subprefix, traffic for it still follows the covering
+// prefix's route. If that route's next hop is the neighbor that sent the hijack,
+// the neighbor most likely forwards the subprefix to the attacker, so the
+// subprefix is blackholed (or, for V3, rerouted) here instead.
+//
+// The lite variants only look for hijacks among the announcements received in the
+// current recv_q. The full variants remember the latest announcement of each prefix
+// from each neighbor, so a hijack from a customer is still caught when the prefix
+// later arrives from a provider
+//
+// Blackholes and preventive announcements are never removed once added, even if the
+// hijack or the covering route is later withdrawn. Scenarios only announce once and
+// never withdraw, so this matches BGPy, but policies that withdraw routes shouldn't
+// be combined with ROV++
+#[derive(Debug, Clone)]
+pub struct ROVPP {
+    pub bgp: BGP,
+    pub version: ROVPPVersion,
+    pub lite: bool,
+    // The latest announcement received from each neighbor for each prefix, along with
+    // the relationship it came in on, including invalid ones. A BTreeMap so that
+    // candidates are always checked in the same order. Always empty for the lite variants
+    pub received: BTreeMap<(Option<u32>, Prefix), (Announcement, Relationships)>,
+    // Subprefixes that have a preventive announcement in the local RIB
+    pub preventive_prefixes: HashSet<Prefix>,
+}
+
+impl ROVPP {
+    pub fn new(asn: u32, version: ROVPPVersion, lite: bool) -> Self {
+        Self {
+            bgp: BGP::new(asn),
+            version,
+            lite,
+            received: BTreeMap::new(),
+            preventive_prefixes: HashSet::new(),
+        }
+    }
+
+    // Adds a blackhole or preventive announcement for every hijacked subprefix
+    // of a route in the local RIB
+    fn add_blackholes(&mut self, candidates: &[(Announcement, Relationships)]) {
+        let mut new_anns = Vec::new();
+
+        for ann in self.bgp.local_rib.data.values() {
+            if ann.rovpp_blackhole || self.preventive_prefixes.contains(&ann.prefix) {
+                continue;
+            }
+
+            for (hijack, hijack_rel) in candidates {
+                if !hijack.invalid_by_roa()
+                    || !hijack.prefix.is_subprefix_of(&ann.prefix)
+                    || hijack.recv_neighbor_asn != ann.recv_neighbor_asn
+                {
+                    continue;
+                }
+                // Never replace a real route (or a preventive announcement) for the subprefix
+                let existing = self.bgp.local_rib.data.get(&hijack.prefix);
+                if existing.is_some_and(|existing| !existing.rovpp_blackhole) {
+                    continue;
+                }
+
+                let alternative = match self.version {
+                    ROVPPVersion::V3 => self.alternative_route(&ann.prefix, &hijack.prefix, candidates),
+                    _ => None,
+                };
+                new_anns.push(match alternative {
+                    Some(alternative) => Announcement {
+                        prefix: hijack.prefix,
+                        ..alternative
+                    },
+                    None => Announcement {
+                        as_path: vec![self.bgp.asn],
+                        next_hop_asn: Some(self.bgp.asn),
+                        seed_asn: None,
+                        recv_relationship: hijack_rel.clone(),
+                        bgpsec_next_asn: None,
+                        bgpsec_as_path: Vec::new(),
+                        rovpp_blackhole: true,
+                        ..hijack.clone()
+                    },
+                });
+            }
+        }
+
+        for ann in new_anns {
+            if !ann.rovpp_blackhole {
+                self.preventive_prefixes.insert(ann.prefix);
+            }
+            self.bgp.local_rib.add_ann(ann);
+        }
+    }
+
+    // Best valid route for prefix from a neighbor that didn't send an invalid subprefix announcement
+    fn alternative_route(&self, prefix: &Prefix, subprefix: &Prefix, candidates: &[(Announcement, Relationships)]) -> Option<Announcement> {
+        let hijacking_neighbors: HashSet<u32> = candidates
+            .iter()
+            .filter(|(ann, _)| ann.prefix == *subprefix && ann.invalid_by_roa())
+            .filter_map(|(ann, _)| ann.recv_neighbor_asn)
+            .collect();
+
+        let mut best: Option<Announcement> = None;
+        for (ann, rel) in candidates {
+            let from_hijacker = ann.recv_neighbor_asn.is_some_and(|asn| hijacking_neighbors.contains(&asn));
+            if ann.prefix != *prefix || ann.rovpp_blackhole || from_hijacker || !self.valid_ann(ann, rel.clone()) {
+                continue;
+            }
+            let processed = self.copy_and_process(ann, rel.clone());
+            if best.as_ref().is_none_or(|current| self.is_better_ann(current, &processed)) {
+                best = Some(processed);
+            }
+        }
+        best
+    }
+}
+
+impl Policy for ROVPP {
+    fn name(&self) -> &'static str {
+        match (self.version, self.lite) {
+            (ROVPPVersion::V1, false) => "ROV++V1",
+            (ROVPPVersion::V1, true) => "ROV++V1Lite",
+            (ROVPPVersion::V2, false) => "ROV++V2",
+            (ROVPPVersion::V2, true) => "ROV++V2Lite",
+            (ROVPPVersion::V3, false) => "ROV++V3",
+            (ROVPPVersion::V3, true) => "ROV++V3Lite",
+        }
+    }
+
+    fn bgp(&self) -> &BGP {
+        &self.bgp
+    }
+
+    fn bgp_mut(&mut self) -> &mut BGP {
+        &mut self.bgp
+    }
+
+    fn clone_box(&self) -> Box<dyn Policy> {
+        Box::new(self.clone())
+    }
+
+    fn process_incoming_anns(&mut self, from_rel: Relationships, reset_q: bool) {
+        let recv_q = self.bgp.recv_q.data.clone();
+        let batch: Vec<(Announcement, Relationships)> = recv_q
+            .values()
+            .flatten()
+            .map(|ann| (ann.clone(), from_rel.clone()))
+            .collect();
+
+        // ROV drops invalid announcements before the BGP decision process,
+        // they're only kept around to find hijacked subprefixes
+        for ann_list in self.bgp.recv_q.data.values_mut() {
+            ann_list.retain(|ann| !ann.invalid_by_roa());
+        }
+        Policy::process_incoming_anns(&mut self.bgp, from_rel, true);
+
+        if self.lite {
+            self.add_blackholes(&batch);
+        } else {
+            for (ann, rel) in batch {
+                let key = (ann.recv_neighbor_asn, ann.prefix);
+                if ann.withdraw {
+                    self.received.remove(&key);
+                } else {
+                    self.received.insert(key, (ann, rel));
+                }
+            }
+            let received: Vec<(Announcement, Relationships)> = self.received.values().cloned().collect();
+            self.add_blackholes(&received);
+        }
+
+        if !reset_q {
+            self.bgp.recv_q.data = recv_q;
+        }
+    }
+
+    fn valid_ann(&self, ann: &Announcement, recv_relationship: Relationships) -> bool {
+        !ann.invalid_by_roa() && self.bgp.valid_ann(ann, recv_relationship)
+    }
+
+    // Blackholes and preventive announcements are only ever sent to customers
+    fn process_outgoing_ann(&self, neighbor: &AutonomousSystem, ann: &Announcement, rel_type: Relationships, _send_rels: &HashSet<Relationships>, outbox: &mut Vec<(AsIdx, Announcement)>) {
+        let customers_only = ann.rovpp_blackhole || self.preventive_prefixes.contains(&ann.prefix);
+        if customers_only && (self.version == ROVPPVersion::V1 || rel_type != Relationships::CUSTOMERS) {
+            return;
+        }
+        outbox.push((neighbor.idx, ann.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::as_graphs::caida_as_graph::as_graph::ASGraph;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink};
+    use crate::simulation_engine::SimulationEngine;
+    use crate::simulation_framework::scenarios::scenario::subprefix;
+    use crate::simulation_framework::scenarios::{Scenario, ScenarioConfig, SubprefixHijack};
+
+    // Victim 5 and attacker 6 are customers of 3, which is a customer of 1. 1 has
+    // customer 7 and provider 8, and 8 is also a provider of the victim. Only 1
+    // adopts (besides the victim), so it gets both the prefix and the hijack from 3
+    fn run(version: ROVPPVersion, lite: bool) -> SimulationEngine {
+        let as_graph = ASGraph::new(ASGraphInfo {
+            customer_provider_links: [(5, 3), (6, 3), (3, 1), (7, 1), (5, 8), (1, 8)]
+                .into_iter()
+                .map(|(customer_asn, provider_asn)| CustomerProviderLink { customer_asn, provider_asn })
+                .collect(),
+            peer_links: HashSet::new(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        });
+        let config = ScenarioConfig::new(
+            HashSet::from([5]),
+            HashSet::from([6]),
+            HashSet::from([1]),
+            Arc::new(move |_, _| Arc::new(move |asn| Box::new(ROVPP::new(asn, version, lite)) as Box<dyn Policy>)),
+        );
+        let scenario = SubprefixHijack::new(config);
+        let mut engine = scenario.setup_engine(Arc::new(as_graph));
+        assert!(scenario.run(&mut engine).is_some());
+        engine
+    }
+
+    fn subprefix_route(engine: &SimulationEngine, asn: u32) -> Option<Announcement> {
+        engine.policy(asn).unwrap().local_rib().data.get(&subprefix()).cloned()
+    }
+
+    #[test]
+    fn v1_blackholes_without_announcing() {
+        let engine = run(ROVPPVersion::V1, false);
+        let blackhole = subprefix_route(&engine, 1).unwrap();
+        assert!(blackhole.rovpp_blackhole);
+        assert_eq!(blackhole.as_path, vec![1]);
+        assert!(subprefix_route(&engine, 7).is_none());
+    }
+
+    #[test]
+    fn v2_announces_blackholes_to_customers() {
+        let engine = run(ROVPPVersion::V2, false);
+        assert!(subprefix_route(&engine, 1).unwrap().rovpp_blackhole);
+        let blackhole = subprefix_route(&engine, 7).unwrap();
+        assert!(blackhole.rovpp_blackhole);
+        assert_eq!(blackhole.as_path, vec![7, 1]);
+        // Never to providers
+        assert!(subprefix_route(&engine, 8).is_none());
+    }
+
+    #[test]
+    fn v3_reroutes_through_neighbors_that_did_not_hijack() {
+        // The provider route arrives in a later recv_q than the hijack, so only
+        // the full variant still knows about the hijack when it can reroute
+        let route = subprefix_route(&run(ROVPPVersion::V3, false), 1).unwrap();
+        assert!(!route.rovpp_blackhole);
+        assert_eq!(route.as_path, vec![1, 8, 5]);
+
+        let route = subprefix_route(&run(ROVPPVersion::V3, true), 1).unwrap();
+        assert!(route.rovpp_blackhole);
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use ipnet::IpNet;
+
+// An IPv4 or IPv6 prefix (network + length).
+// Host bits are always zeroed, so "1.2.3.4/16" and "1.2.0.0/016" both
+// parse to the same Prefix as "1.2.0.0/16"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Prefix {
+    net: IpNet,
+}
+
+impl Prefix {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, PrefixParseError> {
+        let net = IpNet::new(addr, prefix_len)
+            .map_err(|_| PrefixParseError(format!("invalid prefix length {} for {}", prefix_len, addr)))?;
+        Ok(Self { net: net.trunc() })
+    }
+
+    pub fn network(&self) -> IpAddr {
+        self.net.network()
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.net.prefix_len()
+    }
+
+    pub fn is_ipv4(&self) -> bool {
+        matches!(self.net, IpNet::V4(_))
+    }
+
+    pub fn is_ipv6(&self) -> bool {
+        matches!(self.net, IpNet::V6(_))
+    }
+
+    // True if other is equal to or more specific than self
+    pub fn contains(&self, other: &Prefix) -> bool {
+        self.net.contains(&other.net)
+    }
+
+    pub fn contains_addr(&self, addr: &IpAddr) -> bool {
+        self.net.contains(addr)
+    }
+
+    // True if self is strictly more specific than other
+    pub fn is_subprefix_of(&self, other: &Prefix) -> bool {
+        other.contains(self) && self != other
+    }
+
+    // Two prefixes overlap if and only if one contains the other
+    pub fn overlaps(&self, other: &Prefix) -> bool {
+        self.contains(other) || other.contains(self)
+    }
+}
+
+impl FromStr for Prefix {
+    type Err = PrefixParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = s
+            .trim()
+            .split_once('/')
+            .ok_or_else(|| PrefixParseError(format!("{:?} is missing a prefix length", s)))?;
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|_| PrefixParseError(format!("{:?} has an invalid address", s)))?;
+        let prefix_len: u8 = prefix_len
+            .parse()
+            .map_err(|_| PrefixParseError(format!("{:?} has an invalid prefix length", s)))?;
+        Prefix::new(addr, prefix_len)
+    }
+}
+
+impl fmt::Display for Prefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.net)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixParseError(pub String);
+
+impl fmt::Display for PrefixParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid prefix: {}", self.0)
+    }
+}
+
+impl std::error::Error for PrefixParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(s: &str) -> Prefix {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing_zeroes_host_bits() {
+        assert_eq!(p("1.2.3.4/16"), p("1.2.0.0/16"));
+        assert_eq!(p(" 1.2.0.0/016 "), p("1.2.0.0/16"));
+        assert_eq!(p("1.2.3.4/16").to_string(), "1.2.0.0/16");
+        assert_eq!(p("1.2.3.4/16").prefix_len(), 16);
+        assert!(p("1.2.0.0/16").is_ipv4());
+        assert!(p("2001:db8::1/32").is_ipv6());
+        assert_eq!(p("2001:db8::1/32").to_string(), "2001:db8::/32");
+    }
+
+    #[test]
+    fn parsing_errors() {
+        assert!("1.2.0.0".parse::<Prefix>().is_err());
+        assert!("1.2.0/16".parse::<Prefix>().is_err());
+        assert!("1.2.0.0/x".parse::<Prefix>().is_err());
+        assert!("1.2.0.0/33".parse::<Prefix>().is_err());
+        assert!("2001:db8::/129".parse::<Prefix>().is_err());
+    }
+
+    #[test]
+    fn containment() {
+        assert!(p("1.2.0.0/16").contains(&p("1.2.3.0/24")));
+        assert!(p("1.2.0.0/16").contains(&p("1.2.0.0/16")));
+        assert!(!p("1.2.3.0/24").contains(&p("1.2.0.0/16")));
+        assert!(!p("1.2.0.0/16").contains(&p("1.3.0.0/24")));
+        assert!(!p("0.0.0.0/0").contains(&p("::/128")));
+
+        assert!(p("1.2.3.0/24").is_subprefix_of(&p("1.2.0.0/16")));
+        assert!(!p("1.2.0.0/16").is_subprefix_of(&p("1.2.0.0/16")));
+        assert!(!p("1.2.0.0/16").is_subprefix_of(&p("1.2.3.0/24")));
+
+        assert!(p("1.2.0.0/16").overlaps(&p("1.2.3.0/24")));
+        assert!(p("1.2.3.0/24").overlaps(&p("1.2.0.0/16")));
+        assert!(!p("1.2.0.0/16").overlaps(&p("1.3.0.0/16")));
+        assert!(p("1.2.0.0/16").contains_addr(&"1.2.255.255".parse().unwrap()));
+    }
+}
@@ -0,0 +1,149 @@
+use crate::simulation_engine::prefix::Prefix;
+
+// Ordered from best to worst so that the best outcome across ROAs is the min
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ROAValidity {
+    VALID = 0,
+    UNKNOWN = 1,
+    INVALID_LENGTH = 2,
+    INVALID_ORIGIN = 3,
+    INVALID_LENGTH_AND_ORIGIN = 4,
+}
+
+impl ROAValidity {
+    pub fn is_invalid(&self) -> bool {
+        matches!(
+            self,
+            ROAValidity::INVALID_LENGTH | ROAValidity::INVALID_ORIGIN | ROAValidity::INVALID_LENGTH_AND_ORIGIN
+        )
+    }
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ROARouted {
+    ROUTED = 0,
+    UNKNOWN = 1,
+    // A ROA with origin 0 means the prefix should never be announced
+    NON_ROUTED = 2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ROA {
+    pub prefix: Prefix,
+    pub origin: u32,
+    pub max_length: u8,
+}
+
+impl ROA {
+    // max_length defaults to the length of the ROA prefix itself
+    pub fn new(prefix: Prefix, origin: u32, max_length: Option<u8>) -> Self {
+        let max_length = max_length.unwrap_or(prefix.prefix_len());
+        Self {
+            prefix,
+            origin,
+            max_length,
+        }
+    }
+
+    pub fn covers(&self, prefix: &Prefix) -> bool {
+        self.prefix.contains(prefix)
+    }
+
+    pub fn routed(&self) -> ROARouted {
+        if self.origin == 0 {
+            ROARouted::NON_ROUTED
+        } else {
+            ROARouted::ROUTED
+        }
+    }
+
+    pub fn get_validity(&self, prefix: &Prefix, origin: u32) -> ROAValidity {
+        if !self.covers(prefix) {
+            return ROAValidity::UNKNOWN;
+        }
+
+        let invalid_length = prefix.prefix_len() > self.max_length;
+        let invalid_origin = origin != self.origin;
+        match (invalid_length, invalid_origin) {
+            (false, false) => ROAValidity::VALID,
+            (true, false) => ROAValidity::INVALID_LENGTH,
+            (false, true) => ROAValidity::INVALID_ORIGIN,
+            (true, true) => ROAValidity::INVALID_LENGTH_AND_ORIGIN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ROASet {
+    pub roas: Vec<ROA>,
+}
+
+impl ROASet {
+    pub fn new(roas: Vec<ROA>) -> Self {
+        Self { roas }
+    }
+
+    pub fn add_roa(&mut self, roa: ROA) {
+        self.roas.push(roa);
+    }
+
+    // Validity is the best outcome across all covering ROAs,
+    // and routed comes from the ROA that produced that outcome
+    pub fn get_roa_outcome(&self, prefix: &Prefix, origin: u32) -> (ROAValidity, ROARouted) {
+        self.roas
+            .iter()
+            .filter(|roa| roa.covers(prefix))
+            .map(|roa| (roa.get_validity(prefix, origin), roa.routed()))
+            .min()
+            .unwrap_or((ROAValidity::UNKNOWN, ROARouted::UNKNOWN))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(s: &str) -> Prefix {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn roa_validity() {
+        let roas = ROASet::new(vec![ROA::new(p("1.2.0.0/16"), 1, None)]);
+        assert_eq!(roas.get_roa_outcome(&p("1.2.0.0/16"), 1), (ROAValidity::VALID, ROARouted::ROUTED));
+        assert_eq!(roas.get_roa_outcome(&p("1.2.0.0/16"), 2), (ROAValidity::INVALID_ORIGIN, ROARouted::ROUTED));
+        assert_eq!(roas.get_roa_outcome(&p("1.2.3.0/24"), 1), (ROAValidity::INVALID_LENGTH, ROARouted::ROUTED));
+        assert_eq!(
+            roas.get_roa_outcome(&p("1.2.3.0/24"), 2),
+            (ROAValidity::INVALID_LENGTH_AND_ORIGIN, ROARouted::ROUTED)
+        );
+        // No covering ROA
+        assert_eq!(roas.get_roa_outcome(&p("1.0.0.0/8"), 1), (ROAValidity::UNKNOWN, ROARouted::UNKNOWN));
+        assert_eq!(roas.get_roa_outcome(&p("5.6.0.0/16"), 1), (ROAValidity::UNKNOWN, ROARouted::UNKNOWN));
+    }
+
+    #[test]
+    fn max_length_allows_more_specifics() {
+        let roa = ROA::new(p("1.2.0.0/16"), 1, Some(24));
+        assert_eq!(roa.get_validity(&p("1.2.3.0/24"), 1), ROAValidity::VALID);
+        assert_eq!(roa.get_validity(&p("1.2.3.0/25"), 1), ROAValidity::INVALID_LENGTH);
+    }
+
+    #[test]
+    fn best_outcome_across_roas() {
+        let roas = ROASet::new(vec![ROA::new(p("1.2.0.0/16"), 1, None), ROA::new(p("1.2.3.0/24"), 2, None)]);
+        assert_eq!(roas.get_roa_outcome(&p("1.2.3.0/24"), 2), (ROAValidity::VALID, ROARouted::ROUTED));
+        assert_eq!(roas.get_roa_outcome(&p("1.2.3.0/24"), 1), (ROAValidity::INVALID_LENGTH, ROARouted::ROUTED));
+    }
+
+    #[test]
+    fn origin_zero_is_non_routed() {
+        let roas = ROASet::new(vec![ROA::new(p("1.2.0.0/16"), 0, None)]);
+        assert_eq!(
+            roas.get_roa_outcome(&p("1.2.0.0/16"), 1),
+            (ROAValidity::INVALID_ORIGIN, ROARouted::NON_ROUTED)
+        );
+    }
+}
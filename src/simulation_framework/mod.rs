@@ -0,0 +1,10 @@
+pub mod outcomes;
+pub mod scenarios;
+pub mod simulation;
+
+pub use outcomes::{Outcomes, OutcomeCounts, trace_data_plane, count_outcomes_by_group};
+pub use scenarios::{
+    Scenario, ScenarioConfig, PrefixHijack, SubprefixHijack, NonRoutedPrefixHijack,
+    ForgedOriginPrefixHijack, AccidentalRouteLeak, ScenarioConstructor, ScenarioSpec,
+};
+pub use simulation::{Simulation, SimulationResult, TrialConvergence};
@@ -0,0 +1,148 @@
+use std::collections::{BTreeMap, HashSet};
+use std::net::IpAddr;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::AsIdx;
+use crate::simulation_engine::SimulationEngine;
+use crate::simulation_framework::scenarios::Scenario;
+
+// Where traffic from an AS ends up in the data plane
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Outcomes {
+    ATTACKER_SUCCESS = 0,
+    VICTIM_SUCCESS = 1,
+    // No route, a forwarding loop, or a route that ends at a non-victim origin
+    DISCONNECTED = 2,
+    // Dropped by an AS that deliberately blackholed the destination (ROV++)
+    BLACKHOLED = 3,
+}
+
+// Follows next hops through every AS's LocalRIB (using longest prefix match on dest_addr)
+// to find where traffic sent towards dest_addr ends up.
+// Returned in parallel with the ASGraph, indexed by AsIdx
+pub fn trace_data_plane(engine: &SimulationEngine, scenario: &dyn Scenario, dest_addr: &IpAddr) -> Vec<Outcomes> {
+    let as_graph = &engine.as_graph;
+    let config = scenario.config();
+    let mut outcomes: Vec<Option<Outcomes>> = vec![None; as_graph.len()];
+
+    for start in as_graph.iter() {
+        // Every AS on the path shares the outcome of wherever the path terminates
+        let mut path: Vec<AsIdx> = Vec::new();
+        let mut on_path: HashSet<AsIdx> = HashSet::new();
+        let mut idx = start.idx;
+
+        let outcome = loop {
+            if let Some(outcome) = outcomes[idx.index()] {
+                break outcome;
+            }
+            if !on_path.insert(idx) {
+                break Outcomes::DISCONNECTED;
+            }
+            path.push(idx);
+
+            let asn = as_graph[idx].asn;
+            if config.attacker_asns.contains(&asn) {
+                break Outcomes::ATTACKER_SUCCESS;
+            }
+            if config.victim_asns.contains(&asn) {
+                break Outcomes::VICTIM_SUCCESS;
+            }
+
+            let policy = &engine.policies[idx.index()];
+            let best_ann = policy.local_rib().longest_prefix_match(dest_addr);
+            // Only the AS that created the blackhole drops traffic,
+            // ASes that learned it from a neighbor still forward towards it
+            if best_ann.is_some_and(|ann| ann.rovpp_blackhole && ann.next_hop_asn == Some(asn)) {
+                break Outcomes::BLACKHOLED;
+            }
+            let next_hop = best_ann
+                .and_then(|ann| ann.next_hop_asn)
+                .filter(|&next_hop_asn| next_hop_asn != asn)
+                .and_then(|next_hop_asn| as_graph.idx(next_hop_asn));
+            match next_hop {
+                Some(next_idx) => idx = next_idx,
+                None => break Outcomes::DISCONNECTED,
+            }
+        };
+
+        for idx in path {
+            outcomes[idx.index()] = Some(outcome);
+        }
+    }
+
+    outcomes.into_iter().map(|outcome| outcome.unwrap()).collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutcomeCounts {
+    pub attacker_success: usize,
+    pub victim_success: usize,
+    pub disconnected: usize,
+    pub blackholed: usize,
+}
+
+impl OutcomeCounts {
+    pub fn add(&mut self, outcome: Outcomes) {
+        match outcome {
+            Outcomes::ATTACKER_SUCCESS => self.attacker_success += 1,
+            Outcomes::VICTIM_SUCCESS => self.victim_success += 1,
+            Outcomes::DISCONNECTED => self.disconnected += 1,
+            Outcomes::BLACKHOLED => self.blackholed += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.attacker_success + self.victim_success + self.disconnected + self.blackholed
+    }
+
+    pub fn get(&self, outcome: Outcomes) -> usize {
+        match outcome {
+            Outcomes::ATTACKER_SUCCESS => self.attacker_success,
+            Outcomes::VICTIM_SUCCESS => self.victim_success,
+            Outcomes::DISCONNECTED => self.disconnected,
+            Outcomes::BLACKHOLED => self.blackholed,
+        }
+    }
+
+    // Percent of the group with this outcome, 0 for empty groups
+    pub fn percent(&self, outcome: Outcomes) -> f64 {
+        if self.total() == 0 {
+            0.0
+        } else {
+            100.0 * self.get(outcome) as f64 / self.total() as f64
+        }
+    }
+}
+
+// Outcome counts for each of the ASGraph's as_groups, keyed by group name.
+// Victims and attackers are left out since their outcome is fixed by the scenario
+pub fn count_outcomes_by_group(
+    as_graph: &ASGraph,
+    scenario: &dyn Scenario,
+    outcomes: &[Outcomes],
+) -> BTreeMap<String, OutcomeCounts> {
+    let config = scenario.config();
+    let mut group_counts: Vec<(String, &HashSet<u32>, OutcomeCounts)> = as_graph
+        .as_groups
+        .iter()
+        .map(|(group, asns)| (group.to_string(), asns, OutcomeCounts::default()))
+        .collect();
+
+    for as_obj in as_graph {
+        if config.victim_asns.contains(&as_obj.asn) || config.attacker_asns.contains(&as_obj.asn) {
+            continue;
+        }
+
+        let outcome = outcomes[as_obj.idx.index()];
+        for (_, asns, counts) in &mut group_counts {
+            if asns.contains(&as_obj.asn) {
+                counts.add(outcome);
+            }
+        }
+    }
+    group_counts
+        .into_iter()
+        .map(|(group, _, counts)| (group, counts))
+        .collect()
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use crate::as_graphs::caida_as_graph::bgp::Announcement;
+use crate::as_graphs::ASGroup;
+use crate::simulation_engine::policies::{Policy, PolicyFactory};
+use crate::simulation_engine::policies::route_leaker::{LeakBehavior, RouteLeaker};
+use crate::simulation_framework::scenarios::scenario::{victim_anns, Scenario, ScenarioConfig};
+use crate::simulation_framework::scenarios::scenario_spec::ScenarioSpec;
+
+// Victims originate the prefix and each attacker (the leaker) runs a RouteLeaker,
+// re-exporting the route it learned from a provider or peer to its other providers
+// and peers, violating valley-free export. Leakers need a second provider or a peer
+// to leak to, so spec draws attackers from the multihomed group
+pub struct AccidentalRouteLeak {
+    pub config: ScenarioConfig,
+    pub behavior: LeakBehavior,
+}
+
+impl AccidentalRouteLeak {
+    pub fn new(config: ScenarioConfig) -> Self {
+        Self::with_behavior(config, LeakBehavior::default())
+    }
+
+    pub fn with_behavior(config: ScenarioConfig, behavior: LeakBehavior) -> Self {
+        Self { config, behavior }
+    }
+
+    // ScenarioSpec::new, but with attackers drawn from MULTIHOMED
+    // since stubs have nowhere to leak to
+    pub fn spec(label: &str, adopt_policy: PolicyFactory) -> ScenarioSpec {
+        ScenarioSpec {
+            attacker_group: ASGroup::MULTIHOMED,
+            ..ScenarioSpec::new(
+                label,
+                Arc::new(|config| Box::new(AccidentalRouteLeak::new(config)) as Box<dyn Scenario>),
+                adopt_policy,
+            )
+        }
+    }
+}
+
+impl Scenario for AccidentalRouteLeak {
+    fn name(&self) -> &'static str {
+        "AccidentalRouteLeak"
+    }
+
+    fn config(&self) -> &ScenarioConfig {
+        &self.config
+    }
+
+    fn get_announcements(&self) -> Vec<Announcement> {
+        victim_anns(&self.config)
+    }
+
+    fn attacker_policy(&self, asn: u32) -> Box<dyn Policy> {
+        Box::new(RouteLeaker::new(asn, self.behavior.clone()))
+    }
+
+    // The leaker only learns the route once it reaches it going down to customers,
+    // so the leak itself goes out in the second round
+    fn min_propagation_rounds(&self) -> u32 {
+        2
+    }
+}
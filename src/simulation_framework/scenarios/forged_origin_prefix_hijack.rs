@@ -0,0 +1,38 @@
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, Relationships};
+use crate::simulation_framework::scenarios::scenario::{prefix, victim_anns, Scenario, ScenarioConfig};
+
+// Attackers claim to be a neighbor of the victim by appending the victim's ASN
+// to their path. The origin is then correct, so ROV considers it valid
+pub struct ForgedOriginPrefixHijack {
+    pub config: ScenarioConfig,
+}
+
+impl ForgedOriginPrefixHijack {
+    pub fn new(config: ScenarioConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Scenario for ForgedOriginPrefixHijack {
+    fn name(&self) -> &'static str {
+        "ForgedOriginPrefixHijack"
+    }
+
+    fn config(&self) -> &ScenarioConfig {
+        &self.config
+    }
+
+    fn get_announcements(&self) -> Vec<Announcement> {
+        let victim_asn = *self
+            .config
+            .sorted_victim_asns()
+            .first()
+            .expect("ForgedOriginPrefixHijack requires a victim");
+
+        let mut anns = victim_anns(&self.config);
+        for asn in self.config.sorted_attacker_asns() {
+            anns.push(Announcement::new(prefix(), vec![asn, victim_asn], Relationships::ORIGIN));
+        }
+        anns
+    }
+}
@@ -0,0 +1,15 @@
+pub mod scenario;
+pub mod scenario_spec;
+pub mod prefix_hijack;
+pub mod subprefix_hijack;
+pub mod non_routed_prefix_hijack;
+pub mod forged_origin_prefix_hijack;
+pub mod accidental_route_leak;
+
+pub use scenario::{Scenario, ScenarioConfig, DEFAULT_MAX_PROPAGATION_ROUNDS, PREFIX, SUBPREFIX};
+pub use scenario_spec::{ScenarioConstructor, ScenarioSpec};
+pub use prefix_hijack::PrefixHijack;
+pub use subprefix_hijack::SubprefixHijack;
+pub use non_routed_prefix_hijack::NonRoutedPrefixHijack;
+pub use forged_origin_prefix_hijack::ForgedOriginPrefixHijack;
+pub use accidental_route_leak::AccidentalRouteLeak;
@@ -0,0 +1,37 @@
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, Relationships};
+use crate::simulation_engine::roa::{ROA, ROASet};
+use crate::simulation_framework::scenarios::scenario::{prefix, Scenario, ScenarioConfig};
+
+// Attackers originate a prefix that has a ROA but is never announced.
+// The ROA has origin 0, so any announcement of the prefix is invalid
+pub struct NonRoutedPrefixHijack {
+    pub config: ScenarioConfig,
+}
+
+impl NonRoutedPrefixHijack {
+    pub fn new(config: ScenarioConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Scenario for NonRoutedPrefixHijack {
+    fn name(&self) -> &'static str {
+        "NonRoutedPrefixHijack"
+    }
+
+    fn config(&self) -> &ScenarioConfig {
+        &self.config
+    }
+
+    fn get_announcements(&self) -> Vec<Announcement> {
+        self.config
+            .sorted_attacker_asns()
+            .into_iter()
+            .map(|asn| Announcement::new(prefix(), vec![asn], Relationships::ORIGIN))
+            .collect()
+    }
+
+    fn get_roas(&self) -> ROASet {
+        ROASet::new(vec![ROA::new(prefix(), 0, None)])
+    }
+}
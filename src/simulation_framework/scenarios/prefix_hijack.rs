@@ -0,0 +1,31 @@
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, Relationships};
+use crate::simulation_framework::scenarios::scenario::{prefix, victim_anns, Scenario, ScenarioConfig};
+
+// Attackers originate the same prefix as the victims
+pub struct PrefixHijack {
+    pub config: ScenarioConfig,
+}
+
+impl PrefixHijack {
+    pub fn new(config: ScenarioConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Scenario for PrefixHijack {
+    fn name(&self) -> &'static str {
+        "PrefixHijack"
+    }
+
+    fn config(&self) -> &ScenarioConfig {
+        &self.config
+    }
+
+    fn get_announcements(&self) -> Vec<Announcement> {
+        let mut anns = victim_anns(&self.config);
+        for asn in self.config.sorted_attacker_asns() {
+            anns.push(Announcement::new(prefix(), vec![asn], Relationships::ORIGIN));
+        }
+        anns
+    }
+}
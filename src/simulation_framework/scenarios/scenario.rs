@@ -0,0 +1,290 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use crate::as_graphs::ASGraph;
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, BGP, Relationships};
+use crate::simulation_engine::SimulationEngine;
+use crate::simulation_engine::decision_process::DecisionProcess;
+use crate::simulation_engine::policies::{Policy, PolicyConstructor, PolicyFactory};
+use crate::simulation_engine::prefix::Prefix;
+use crate::simulation_engine::roa::{ROA, ROASet};
+
+// Prefixes used by the built-in scenarios
+pub const PREFIX: &str = "1.2.0.0/16";
+pub const SUBPREFIX: &str = "1.2.3.0/24";
+
+// Scenarios that haven't converged after this many propagation rounds are given up on.
+// A route is replaced whenever its neighbor sends a new one for the prefix, but plain BGP
+// has no explicit withdrawals: a route the neighbor stops exporting entirely is kept
+// (use BGPFull for that), so convergence means nothing changed, not that every route is current
+pub const DEFAULT_MAX_PROPAGATION_ROUNDS: u32 = 10;
+
+pub fn prefix() -> Prefix {
+    PREFIX.parse().unwrap()
+}
+
+pub fn subprefix() -> Prefix {
+    SUBPREFIX.parse().unwrap()
+}
+
+// Who is involved in a single run of a scenario.
+// Victims always adopt, since policies like Path-End, ASPA and BGPsec protect nothing
+// unless the origin registers or signs. Attackers never adopt
+#[derive(Clone)]
+pub struct ScenarioConfig {
+    pub victim_asns: HashSet<u32>,
+    pub attacker_asns: HashSet<u32>,
+    pub adopting_asns: HashSet<u32>,
+    pub adopt_policy: PolicyFactory,
+    // What every other AS runs, e.g. BGPFull to model withdrawals
+    pub base_policy: PolicyConstructor,
+    // How every AS selects routes, adjusted by each policy's decision_steps
+    pub decision_process: DecisionProcess,
+}
+
+impl ScenarioConfig {
+    pub fn new(
+        victim_asns: HashSet<u32>,
+        attacker_asns: HashSet<u32>,
+        adopting_asns: HashSet<u32>,
+        adopt_policy: PolicyFactory,
+    ) -> Self {
+        Self {
+            victim_asns,
+            attacker_asns,
+            adopting_asns,
+            adopt_policy,
+            base_policy: bgp_policy(),
+            decision_process: DecisionProcess::default(),
+        }
+    }
+
+    pub fn is_adopting(&self, asn: u32) -> bool {
+        (self.adopting_asns.contains(&asn) || self.victim_asns.contains(&asn))
+            && !self.attacker_asns.contains(&asn)
+    }
+
+    // Sorted so that announcements are always seeded in the same order
+    pub fn sorted_victim_asns(&self) -> Vec<u32> {
+        sorted(&self.victim_asns)
+    }
+
+    pub fn sorted_attacker_asns(&self) -> Vec<u32> {
+        sorted(&self.attacker_asns)
+    }
+}
+
+// The default base policy, plain BGP
+pub fn bgp_policy() -> PolicyConstructor {
+    Arc::new(|asn| Box::new(BGP::new(asn)))
+}
+
+fn sorted(asns: &HashSet<u32>) -> Vec<u32> {
+    let mut asns: Vec<u32> = asns.iter().copied().collect();
+    asns.sort_unstable();
+    asns
+}
+
+// An attack (or benign event) to simulate: which announcements get seeded where,
+// which ROAs exist, and anything that happens between propagation rounds
+pub trait Scenario: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn config(&self) -> &ScenarioConfig;
+
+    fn get_announcements(&self) -> Vec<Announcement>;
+
+    // By default every victim has a ROA for the prefix
+    fn get_roas(&self) -> ROASet {
+        ROASet::new(
+            self.config()
+                .sorted_victim_asns()
+                .into_iter()
+                .map(|asn| ROA::new(prefix(), asn, None))
+                .collect(),
+        )
+    }
+
+    // Where data plane traffic is sent: inside the most specific announced prefix
+    fn get_dest_addr(&self) -> IpAddr {
+        self.get_announcements()
+            .iter()
+            .map(|ann| ann.prefix)
+            .max_by_key(|prefix| prefix.prefix_len())
+            .unwrap_or_else(prefix)
+            .network()
+    }
+
+    fn min_propagation_rounds(&self) -> u32 {
+        1
+    }
+
+    fn max_propagation_rounds(&self) -> u32 {
+        DEFAULT_MAX_PROPAGATION_ROUNDS
+    }
+
+    fn post_propagation_hook(&self, _engine: &mut SimulationEngine, _propagation_round: u32) {}
+
+    // Attackers never adopt, but some attacks need them to run a misbehaving policy
+    fn attacker_policy(&self, asn: u32) -> Box<dyn Policy> {
+        (self.config().base_policy)(asn)
+    }
+
+    fn setup_engine(&self, as_graph: Arc<ASGraph>) -> SimulationEngine {
+        let config = self.config();
+        let adopting_asns: HashSet<u32> = as_graph
+            .asns()
+            .filter(|&asn| config.is_adopting(asn))
+            .collect();
+        let adopt_policy = (config.adopt_policy)(&as_graph, &adopting_asns);
+
+        let mut engine = SimulationEngine::with_policies(as_graph, |as_obj| {
+            if config.attacker_asns.contains(&as_obj.asn) {
+                self.attacker_policy(as_obj.asn)
+            } else if adopting_asns.contains(&as_obj.asn) {
+                adopt_policy(as_obj.asn)
+            } else {
+                (config.base_policy)(as_obj.asn)
+            }
+        });
+        engine.set_decision_process(&config.decision_process);
+        engine.roas = self.get_roas();
+        engine.seed_anns(self.get_announcements());
+        engine
+    }
+
+    // Runs propagation rounds on an engine built by setup_engine until a round (hook included)
+    // leaves every LocalRIB unchanged, running at least min_propagation_rounds.
+    // Returns how many rounds it took to converge, not counting the unchanged round,
+    // or None if the LocalRIBs were still changing after max_propagation_rounds
+    fn run(&self, engine: &mut SimulationEngine) -> Option<u32> {
+        let mut local_ribs = engine.local_rib_snapshot();
+        for propagation_round in 0..self.max_propagation_rounds() {
+            engine.run();
+            self.post_propagation_hook(engine, propagation_round);
+
+            let new_local_ribs = engine.local_rib_snapshot();
+            if new_local_ribs == local_ribs && propagation_round + 1 >= self.min_propagation_rounds() {
+                return Some(propagation_round);
+            }
+            local_ribs = new_local_ribs;
+        }
+        None
+    }
+}
+
+// Each victim legitimately originates the prefix
+pub fn victim_anns(config: &ScenarioConfig) -> Vec<Announcement> {
+    config
+        .sorted_victim_asns()
+        .into_iter()
+        .map(|asn| Announcement::new(prefix(), vec![asn], Relationships::ORIGIN))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink};
+    use crate::simulation_engine::decision_process::{DecisionStep, TieBreak};
+    use crate::simulation_engine::policies::{independent_policy, BGPsec, BGPsecPreference};
+
+    // Victim 5, attacker 2 and bystander 4 are all customers of 3
+    fn as_graph() -> ASGraph {
+        ASGraph::new(ASGraphInfo {
+            customer_provider_links: [5, 2, 4]
+                .into_iter()
+                .map(|customer_asn| CustomerProviderLink { customer_asn, provider_asn: 3 })
+                .collect(),
+            peer_links: HashSet::new(),
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::new(),
+        })
+    }
+
+    // The attacker hijacks the prefix only after the victim's route has spread
+    struct LateHijack {
+        config: ScenarioConfig,
+    }
+
+    impl Scenario for LateHijack {
+        fn name(&self) -> &'static str {
+            "LateHijack"
+        }
+
+        fn config(&self) -> &ScenarioConfig {
+            &self.config
+        }
+
+        fn get_announcements(&self) -> Vec<Announcement> {
+            victim_anns(&self.config)
+        }
+
+        fn min_propagation_rounds(&self) -> u32 {
+            2
+        }
+
+        fn post_propagation_hook(&self, engine: &mut SimulationEngine, propagation_round: u32) {
+            if propagation_round == 0 {
+                let hijacks = self
+                    .config
+                    .sorted_attacker_asns()
+                    .into_iter()
+                    .map(|asn| Announcement::new(prefix(), vec![asn], Relationships::ORIGIN))
+                    .collect();
+                engine.seed_anns(hijacks);
+            }
+        }
+    }
+
+    #[test]
+    fn hook_seeds_replace_learned_routes() {
+        let config = ScenarioConfig::new(
+            HashSet::from([5]),
+            HashSet::from([2]),
+            HashSet::new(),
+            independent_policy(|asn| Box::new(BGP::new(asn))),
+        );
+        let scenario = LateHijack { config };
+        let mut engine = scenario.setup_engine(Arc::new(as_graph()));
+        assert!(scenario.run(&mut engine).is_some());
+
+        let route = |asn| engine.policy(asn).unwrap().local_rib().data[&prefix()].as_path.clone();
+        assert_eq!(route(2), vec![2]);
+        // 3 breaks the tie between its customers towards the lower next hop, the attacker
+        assert_eq!(route(3), vec![3, 2]);
+        // 3's new route implicitly replaces the one it sent 4 before
+        assert_eq!(route(4), vec![4, 3, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Seeding conflict")]
+    fn seeding_over_a_seed_panics() {
+        let mut engine = SimulationEngine::new(Arc::new(as_graph()));
+        engine.seed_anns(vec![Announcement::new(prefix(), vec![2], Relationships::ORIGIN)]);
+        engine.seed_anns(vec![Announcement::new(prefix(), vec![2, 9], Relationships::ORIGIN)]);
+    }
+
+    #[test]
+    fn ases_share_the_scenarios_decision_process() {
+        let mut config = ScenarioConfig::new(
+            HashSet::from([5]),
+            HashSet::from([2]),
+            HashSet::from([3]),
+            BGPsec::policy_factory(BGPsecPreference::SECURITY_FIRST),
+        );
+        config.decision_process.tie_break = TieBreak::SEEDED_RANDOM(7);
+        let scenario = LateHijack { config };
+        let engine = scenario.setup_engine(Arc::new(as_graph()));
+        let decision_process = |asn| engine.policy(asn).unwrap().bgp().decision_process.clone();
+
+        assert!(Arc::ptr_eq(&decision_process(4), &decision_process(2)));
+        assert_eq!(decision_process(4).tie_break, TieBreak::SEEDED_RANDOM(7));
+        assert_eq!(
+            decision_process(3).steps,
+            vec![DecisionStep::SECURITY, DecisionStep::LOCAL_PREF, DecisionStep::AS_PATH_LENGTH]
+        );
+        assert_eq!(decision_process(3).tie_break, TieBreak::SEEDED_RANDOM(7));
+    }
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use crate::as_graphs::ASGroup;
+use crate::simulation_engine::decision_process::DecisionProcess;
+use crate::simulation_engine::policies::{PolicyConstructor, PolicyFactory};
+use crate::simulation_framework::scenarios::scenario::{bgp_policy, Scenario, ScenarioConfig};
+
+// Builds a scenario once the victims, attackers and adopters of a trial are known
+pub type ScenarioConstructor = Arc<dyn Fn(ScenarioConfig) -> Box<dyn Scenario> + Send + Sync>;
+
+// Describes a scenario for a Simulation, which samples a
+// ScenarioConfig from it for every trial and percent adoption
+#[derive(Clone)]
+pub struct ScenarioSpec {
+    // Identifies this spec in the results, e.g. "SubprefixHijack ROV"
+    pub label: String,
+    pub scenario: ScenarioConstructor,
+    pub adopt_policy: PolicyFactory,
+    // Passed on to every ScenarioConfig
+    pub base_policy: PolicyConstructor,
+    pub num_victims: usize,
+    pub num_attackers: usize,
+    // Groups that victims and attackers are sampled from
+    pub victim_group: ASGroup,
+    pub attacker_group: ASGroup,
+    // Groups that adopters are sampled from. Each group adopts
+    // at the given percentage independently
+    pub adoption_groups: Vec<ASGroup>,
+    // Passed on to every ScenarioConfig. A SEEDED_RANDOM tie-breaker's seed is
+    // replaced with one drawn for each trial
+    pub decision_process: DecisionProcess,
+}
+
+impl ScenarioSpec {
+    // One victim and one attacker, both stubs or multihomed,
+    // with adoption across stubs, multihomed and transit ASes
+    pub fn new(label: &str, scenario: ScenarioConstructor, adopt_policy: PolicyFactory) -> Self {
+        Self {
+            label: label.to_string(),
+            scenario,
+            adopt_policy,
+            base_policy: bgp_policy(),
+            num_victims: 1,
+            num_attackers: 1,
+            victim_group: ASGroup::STUBS_OR_MH,
+            attacker_group: ASGroup::STUBS_OR_MH,
+            adoption_groups: vec![ASGroup::STUBS, ASGroup::MULTIHOMED, ASGroup::TRANSIT],
+            decision_process: DecisionProcess::default(),
+        }
+    }
+}
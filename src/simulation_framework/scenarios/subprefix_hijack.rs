@@ -0,0 +1,32 @@
+use crate::as_graphs::caida_as_graph::bgp::{Announcement, Relationships};
+use crate::simulation_framework::scenarios::scenario::{subprefix, victim_anns, Scenario, ScenarioConfig};
+
+// Attackers originate a more specific prefix than the victims,
+// so they win through longest prefix match rather than path selection
+pub struct SubprefixHijack {
+    pub config: ScenarioConfig,
+}
+
+impl SubprefixHijack {
+    pub fn new(config: ScenarioConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Scenario for SubprefixHijack {
+    fn name(&self) -> &'static str {
+        "SubprefixHijack"
+    }
+
+    fn config(&self) -> &ScenarioConfig {
+        &self.config
+    }
+
+    fn get_announcements(&self) -> Vec<Announcement> {
+        let mut anns = victim_anns(&self.config);
+        for asn in self.config.sorted_attacker_asns() {
+            anns.push(Announcement::new(subprefix(), vec![asn], Relationships::ORIGIN));
+        }
+        anns
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::as_graphs::{ASGraph, ASGroup};
+use crate::simulation_engine::decision_process::TieBreak;
+use crate::simulation_framework::outcomes::{count_outcomes_by_group, trace_data_plane, Outcomes};
+use crate::simulation_framework::scenarios::{ScenarioConfig, ScenarioSpec};
+
+// The outcomes tracked for every group
+const OUTCOMES: [Outcomes; 4] = [
+    Outcomes::ATTACKER_SUCCESS,
+    Outcomes::VICTIM_SUCCESS,
+    Outcomes::DISCONNECTED,
+    Outcomes::BLACKHOLED,
+];
+
+// Aggregated result for one scenario, percent adoption, AS group and outcome
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationResult {
+    pub scenario: String,
+    pub percent_adopt: f64,
+    pub group: String,
+    pub outcome: String,
+    // Mean percent of the group with this outcome across trials
+    pub mean: f64,
+    // Half-width of the 95% confidence interval of the mean
+    pub ci_95: f64,
+    pub num_trials: usize,
+}
+
+// How many propagation rounds one trial of a scenario took to converge
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrialConvergence {
+    pub scenario: String,
+    pub percent_adopt: f64,
+    pub trial: usize,
+    // Empty if it hadn't converged by the scenario's max_propagation_rounds
+    pub rounds_to_converge: Option<u32>,
+}
+
+// Runs every scenario spec at every percent adoption for num_trials trials,
+// sampling new victims, attackers and adopters each time
+pub struct Simulation {
+    // Shared by every engine, which never mutates it
+    pub as_graph: Arc<ASGraph>,
+    pub scenario_specs: Vec<ScenarioSpec>,
+    // Percentages in [0, 100]
+    pub percent_adoptions: Vec<f64>,
+    pub num_trials: usize,
+    pub output_dir: PathBuf,
+    // Threads that runs are spread across. None uses every core
+    pub num_workers: Option<usize>,
+    // Drives every random choice. Each run of a scenario spec at a percent adoption
+    // in a trial gets its own stream of this seed, so results don't depend on
+    // num_workers or the order runs finish in
+    pub seed: u64,
+}
+
+// (scenario spec index, percent adoption index, group, outcome)
+type SampleKey = (usize, usize, String, Outcomes);
+
+// Everything a single run of a scenario spec produces
+type RunOutput = (Vec<(SampleKey, f64)>, TrialConvergence);
+
+impl Simulation {
+    pub fn new(
+        as_graph: ASGraph,
+        scenario_specs: Vec<ScenarioSpec>,
+        percent_adoptions: Vec<f64>,
+        num_trials: usize,
+        output_dir: PathBuf,
+    ) -> Self {
+        Self {
+            as_graph: Arc::new(as_graph),
+            scenario_specs,
+            percent_adoptions,
+            num_trials,
+            output_dir,
+            num_workers: None,
+            seed: 0,
+        }
+    }
+
+    // Runs all trials and writes results.csv, results.json and convergence.csv to the output_dir.
+    // Every (trial, percent adoption, scenario spec) run is independent, so each one runs
+    // on whichever worker picks it up with its own engine, and samples are merged back in order
+    pub fn run(&self) -> Result<Vec<SimulationResult>, Box<dyn std::error::Error>> {
+        let mut pool_builder = rayon::ThreadPoolBuilder::new();
+        if let Some(num_workers) = self.num_workers {
+            pool_builder = pool_builder.num_threads(num_workers);
+        }
+        let pool = pool_builder.build()?;
+
+        let mut runs = Vec::new();
+        for trial in 0..self.num_trials {
+            for percent_idx in 0..self.percent_adoptions.len() {
+                for spec_idx in 0..self.scenario_specs.len() {
+                    runs.push((trial, percent_idx, spec_idx));
+                }
+            }
+        }
+        let run_outputs: Vec<RunOutput> = pool.install(|| {
+            runs.into_par_iter()
+                .map(|(trial, percent_idx, spec_idx)| {
+                    if percent_idx == 0 && spec_idx == 0 {
+                        log::info!("Running trial {}/{}", trial + 1, self.num_trials);
+                    }
+                    self.run_one(trial, percent_idx, spec_idx)
+                })
+                .collect()
+        });
+
+        let mut samples: BTreeMap<SampleKey, Vec<f64>> = BTreeMap::new();
+        let mut convergence = Vec::new();
+        for (run_samples, run_convergence) in run_outputs {
+            for (key, value) in run_samples {
+                samples.entry(key).or_default().push(value);
+            }
+            convergence.push(run_convergence);
+        }
+
+        let results = self.aggregate(&samples);
+        self.write_results(&results, &convergence)?;
+        Ok(results)
+    }
+
+    // One stream per run, numbered in the order runs are merged back.
+    // ChaCha is portable, so a seed gives the same results on every platform
+    fn run_rng(&self, trial: usize, percent_idx: usize, spec_idx: usize) -> ChaCha8Rng {
+        let run = (trial * self.percent_adoptions.len() + percent_idx) * self.scenario_specs.len() + spec_idx;
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(run as u64);
+        rng
+    }
+
+    fn run_one(&self, trial: usize, percent_idx: usize, spec_idx: usize) -> RunOutput {
+        let spec = &self.scenario_specs[spec_idx];
+        let percent_adopt = self.percent_adoptions[percent_idx];
+        let config = self.sample_scenario_config(&mut self.run_rng(trial, percent_idx, spec_idx), spec, percent_adopt);
+        let scenario = (spec.scenario)(config);
+
+        let mut engine = scenario.setup_engine(self.as_graph.clone());
+        let rounds_to_converge = scenario.run(&mut engine);
+        if rounds_to_converge.is_none() {
+            log::warn!("{} didn't converge in trial {}", spec.label, trial);
+        }
+        let convergence = TrialConvergence {
+            scenario: spec.label.clone(),
+            percent_adopt,
+            trial,
+            rounds_to_converge,
+        };
+
+        let outcomes = trace_data_plane(&engine, scenario.as_ref(), &scenario.get_dest_addr());
+
+        let mut samples = Vec::new();
+        for (group, counts) in count_outcomes_by_group(&self.as_graph, scenario.as_ref(), &outcomes) {
+            for outcome in OUTCOMES {
+                samples.push(((spec_idx, percent_idx, group.clone(), outcome), counts.percent(outcome)));
+            }
+        }
+        (samples, convergence)
+    }
+
+    fn sample_scenario_config<R: Rng>(&self, rng: &mut R, spec: &ScenarioSpec, percent_adopt: f64) -> ScenarioConfig {
+        let attacker_asns = sample(rng, &self.group_asns(&spec.attacker_group), spec.num_attackers, &HashSet::new());
+        let victim_asns = sample(rng, &self.group_asns(&spec.victim_group), spec.num_victims, &attacker_asns);
+
+        let uncountable: HashSet<u32> = attacker_asns.union(&victim_asns).copied().collect();
+        let mut adopting_asns = HashSet::new();
+        for group in &spec.adoption_groups {
+            let pool = self.group_asns(group);
+            let eligible = pool.difference(&uncountable).count();
+            let k = (eligible as f64 * percent_adopt / 100.0).round() as usize;
+            adopting_asns.extend(sample(rng, &pool, k, &uncountable));
+        }
+
+        let mut config = ScenarioConfig::new(victim_asns, attacker_asns, adopting_asns, spec.adopt_policy.clone());
+        config.base_policy = spec.base_policy.clone();
+        config.decision_process = spec.decision_process.clone();
+        // Drawn last, so that adding a seeded tie-breaker doesn't change who gets sampled
+        if let TieBreak::SEEDED_RANDOM(_) = config.decision_process.tie_break {
+            config.decision_process.tie_break = TieBreak::SEEDED_RANDOM(rng.gen());
+        }
+        config
+    }
+
+    fn group_asns(&self, group: &ASGroup) -> HashSet<u32> {
+        self.as_graph
+            .group_asns(group)
+            .unwrap_or_else(|| panic!("Unknown AS group {}", group))
+    }
+
+    fn aggregate(&self, samples: &BTreeMap<SampleKey, Vec<f64>>) -> Vec<SimulationResult> {
+        samples
+            .iter()
+            .map(|((spec_idx, percent_idx, group, outcome), values)| {
+                let (mean, ci_95) = mean_and_ci_95(values);
+                SimulationResult {
+                    scenario: self.scenario_specs[*spec_idx].label.clone(),
+                    percent_adopt: self.percent_adoptions[*percent_idx],
+                    group: group.clone(),
+                    outcome: format!("{:?}", outcome),
+                    mean,
+                    ci_95,
+                    num_trials: values.len(),
+                }
+            })
+            .collect()
+    }
+
+    fn write_results(&self, results: &[SimulationResult], convergence: &[TrialConvergence]) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.output_dir)?;
+
+        let mut writer = csv::Writer::from_path(self.output_dir.join("results.csv"))?;
+        for result in results {
+            writer.serialize(result)?;
+        }
+        writer.flush()?;
+
+        let json_file = File::create(self.output_dir.join("results.json"))?;
+        serde_json::to_writer_pretty(json_file, results)?;
+
+        let mut writer = csv::Writer::from_path(self.output_dir.join("convergence.csv"))?;
+        for trial_convergence in convergence {
+            writer.serialize(trial_convergence)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+// Samples k ASNs from pool, never choosing any in exclude, or every
+// candidate if there are fewer than k (e.g. on a small AS graph).
+// Sorted first so that the result only depends on the rng
+fn sample<R: Rng>(rng: &mut R, pool: &HashSet<u32>, k: usize, exclude: &HashSet<u32>) -> HashSet<u32> {
+    let mut candidates: Vec<u32> = pool.difference(exclude).copied().collect();
+    candidates.sort_unstable();
+    if candidates.len() < k {
+        log::warn!("Can't sample {} ASNs from only {} candidates, taking them all", k, candidates.len());
+    }
+    candidates.choose_multiple(rng, k.min(candidates.len())).copied().collect()
+}
+
+// Uses the sample standard deviation and Student's t, since there are usually
+// only a handful of trials. A single trial has no interval
+fn mean_and_ci_95(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, t_975(values.len() - 1) * variance.sqrt() / n.sqrt())
+}
+
+// Two-sided 95% critical value of Student's t with df degrees of freedom.
+// Tabulated up to 30, then the Cornish-Fisher expansion around the normal's 1.96,
+// which is within 0.001 of the exact value from there on
+fn t_975(df: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160, 2.145, 2.131,
+        2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    if (1..=TABLE.len()).contains(&df) {
+        return TABLE[df - 1];
+    }
+    let z: f64 = 1.959_963_984_540_054;
+    let v = df as f64;
+    z + (z.powi(3) + z) / (4.0 * v)
+        + (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / (96.0 * v.powi(2))
+        + (3.0 * z.powi(7) + 19.0 * z.powi(5) + 17.0 * z.powi(3) - 15.0 * z) / (384.0 * v.powi(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::as_graphs::caida_as_graph::{ASGraphInfo, CustomerProviderLink, PeerLink};
+    use crate::simulation_engine::ROV;
+    use crate::simulation_engine::policies::independent_policy;
+    use crate::simulation_framework::scenarios::{PrefixHijack, Scenario, SubprefixHijack};
+
+    // Two peering tier-1s, five transit ASes below them and forty stubs,
+    // every third of which is multihomed
+    fn as_graph() -> ASGraph {
+        let mut customer_provider_links = HashSet::new();
+        for transit_asn in 10..15 {
+            customer_provider_links.insert(CustomerProviderLink {
+                customer_asn: transit_asn,
+                provider_asn: 100 + transit_asn % 2,
+            });
+        }
+        for stub_asn in 1000..1040 {
+            customer_provider_links.insert(CustomerProviderLink {
+                customer_asn: stub_asn,
+                provider_asn: 10 + stub_asn % 5,
+            });
+            if stub_asn % 3 == 0 {
+                customer_provider_links.insert(CustomerProviderLink {
+                    customer_asn: stub_asn,
+                    provider_asn: 10 + (stub_asn + 1) % 5,
+                });
+            }
+        }
+        let peer_links = HashSet::from([PeerLink { peer1_asn: 100, peer2_asn: 101 }]);
+
+        ASGraph::new(ASGraphInfo {
+            customer_provider_links,
+            peer_links,
+            ixp_asns: HashSet::new(),
+            input_clique_asns: HashSet::from([100, 101]),
+        })
+    }
+
+    fn simulation(output_dir: PathBuf, num_workers: usize) -> Simulation {
+        let rov = independent_policy(|asn| Box::new(ROV::new(asn)));
+        let scenario_specs = vec![
+            ScenarioSpec::new(
+                "SubprefixHijack ROV",
+                Arc::new(|config| Box::new(SubprefixHijack::new(config)) as Box<dyn Scenario>),
+                rov.clone(),
+            ),
+            ScenarioSpec::new(
+                "PrefixHijack ROV",
+                Arc::new(|config| Box::new(PrefixHijack::new(config)) as Box<dyn Scenario>),
+                rov,
+            ),
+        ];
+        let mut simulation = Simulation::new(as_graph(), scenario_specs, vec![10.0, 50.0, 90.0], 8, output_dir);
+        simulation.num_workers = Some(num_workers);
+        simulation.seed = 42;
+        simulation
+    }
+
+    fn read_outputs(output_dir: &std::path::Path) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        (
+            fs::read(output_dir.join("results.csv")).unwrap(),
+            fs::read(output_dir.join("results.json")).unwrap(),
+            fs::read(output_dir.join("convergence.csv")).unwrap(),
+        )
+    }
+
+    #[test]
+    fn same_seed_gives_identical_output_files() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        simulation(first.path().to_path_buf(), 4).run().unwrap();
+        simulation(second.path().to_path_buf(), 4).run().unwrap();
+
+        assert_eq!(read_outputs(first.path()), read_outputs(second.path()));
+    }
+
+    #[test]
+    fn serial_and_parallel_runs_match() {
+        let serial = TempDir::new().unwrap();
+        let parallel = TempDir::new().unwrap();
+        simulation(serial.path().to_path_buf(), 1).run().unwrap();
+        simulation(parallel.path().to_path_buf(), 4).run().unwrap();
+
+        assert_eq!(read_outputs(serial.path()), read_outputs(parallel.path()));
+    }
+
+    #[test]
+    fn seeded_tie_breaks_are_drawn_per_trial() {
+        let output_dir = TempDir::new().unwrap();
+        let mut simulation = simulation(output_dir.path().to_path_buf(), 1);
+        simulation.scenario_specs[0].decision_process.tie_break = TieBreak::SEEDED_RANDOM(0);
+        let tie_break = |trial| {
+            let mut rng = simulation.run_rng(trial, 0, 0);
+            let config = simulation.sample_scenario_config(&mut rng, &simulation.scenario_specs[0], 50.0);
+            config.decision_process.tie_break
+        };
+
+        assert_eq!(tie_break(0), tie_break(0));
+        assert_ne!(tie_break(0), tie_break(1));
+        assert_ne!(tie_break(0), TieBreak::SEEDED_RANDOM(0));
+    }
+
+    #[test]
+    fn sampling_more_than_there_are_takes_every_candidate() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let pool = HashSet::from([1, 2, 3, 4]);
+        assert_eq!(sample(&mut rng, &pool, 10, &HashSet::from([4])), HashSet::from([1, 2, 3]));
+        assert_eq!(sample(&mut rng, &pool, 2, &HashSet::new()).len(), 2);
+    }
+
+    #[test]
+    fn confidence_intervals_use_students_t() {
+        // Ten trials, with a sample variance of 55 / 6
+        let values: Vec<f64> = (1..=10).map(f64::from).collect();
+        let (mean, ci_95) = mean_and_ci_95(&values);
+        assert_eq!(mean, 5.5);
+        assert!((ci_95 - 2.262 * (55.0_f64 / 6.0).sqrt() / 10.0_f64.sqrt()).abs() < 1e-9);
+
+        // The expansion picks up where the table leaves off, and tends to the normal
+        assert!((t_975(31) - 2.040).abs() < 0.001);
+        assert!((t_975(60) - 2.000).abs() < 0.001);
+        assert!((t_975(100_000) - 1.960).abs() < 0.001);
+    }
+}